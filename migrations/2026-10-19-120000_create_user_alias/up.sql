@@ -0,0 +1,12 @@
+-- Previous usernames of a user, written when an upload renames them
+CREATE TABLE `UserAlias` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `userId` VARCHAR(191) NOT NULL,
+    `username` VARCHAR(191) NOT NULL,
+    `changedAt` DATETIME(3) NOT NULL,
+
+    INDEX `UserAlias_userId_idx`(`userId`),
+    INDEX `UserAlias_username_idx`(`username`),
+    PRIMARY KEY (`id`),
+    CONSTRAINT `UserAlias_userId_fkey` FOREIGN KEY (`userId`) REFERENCES `User`(`userId`) ON DELETE CASCADE ON UPDATE CASCADE
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
@@ -0,0 +1,4 @@
+ALTER TABLE `MatchUser` DROP FOREIGN KEY `MatchUser_teamId_fkey`;
+ALTER TABLE `MatchUser` DROP INDEX `MatchUser_teamId_idx`;
+ALTER TABLE `MatchUser` DROP COLUMN `teamId`;
+DROP TABLE `MatchTeam`;
@@ -0,0 +1,16 @@
+-- Teams of duo/squad matches, their members are the MatchUser rows with the team's id
+CREATE TABLE `MatchTeam` (
+    `id` VARCHAR(191) NOT NULL,
+    `name` VARCHAR(191) NOT NULL,
+    `rank` INTEGER NOT NULL,
+    `lifeStatus` VARCHAR(191) NOT NULL,
+    `matchId` VARCHAR(191) NOT NULL,
+
+    INDEX `MatchTeam_matchId_idx`(`matchId`),
+    PRIMARY KEY (`id`),
+    CONSTRAINT `MatchTeam_matchId_fkey` FOREIGN KEY (`matchId`) REFERENCES `Match`(`id`) ON DELETE CASCADE ON UPDATE CASCADE
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+ALTER TABLE `MatchUser` ADD COLUMN `teamId` VARCHAR(191) NULL;
+ALTER TABLE `MatchUser` ADD INDEX `MatchUser_teamId_idx`(`teamId`);
+ALTER TABLE `MatchUser` ADD CONSTRAINT `MatchUser_teamId_fkey` FOREIGN KEY (`teamId`) REFERENCES `MatchTeam`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
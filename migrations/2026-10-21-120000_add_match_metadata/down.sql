@@ -0,0 +1,6 @@
+ALTER TABLE `Match`
+    DROP COLUMN `map`,
+    DROP COLUMN `mode`,
+    DROP COLUMN `startedAt`,
+    DROP COLUMN `durationSecs`,
+    DROP COLUMN `notes`;
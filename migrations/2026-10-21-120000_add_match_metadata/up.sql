@@ -0,0 +1,7 @@
+-- Optional per match details from the `!key: value` lines of a tournament file
+ALTER TABLE `Match`
+    ADD COLUMN `map` VARCHAR(191) NULL,
+    ADD COLUMN `mode` VARCHAR(191) NULL,
+    ADD COLUMN `startedAt` DATETIME(3) NULL,
+    ADD COLUMN `durationSecs` INTEGER NULL,
+    ADD COLUMN `notes` TEXT NULL;
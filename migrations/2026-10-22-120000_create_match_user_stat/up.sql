@@ -0,0 +1,12 @@
+-- Per player match statistics from the `|name=value` fields of a player line
+CREATE TABLE `MatchUserStat` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `matchUserId` VARCHAR(191) NOT NULL,
+    `name` VARCHAR(191) NOT NULL,
+    `value` INTEGER NOT NULL,
+
+    INDEX `MatchUserStat_name_idx`(`name`),
+    UNIQUE INDEX `MatchUserStat_matchUserId_name_key`(`matchUserId`, `name`),
+    PRIMARY KEY (`id`),
+    CONSTRAINT `MatchUserStat_matchUserId_fkey` FOREIGN KEY (`matchUserId`) REFERENCES `MatchUser`(`id`) ON DELETE CASCADE ON UPDATE CASCADE
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
@@ -0,0 +1,4 @@
+ALTER TABLE `Tournament` DROP FOREIGN KEY `Tournament_seasonId_fkey`;
+ALTER TABLE `Tournament` DROP INDEX `Tournament_seasonId_idx`;
+ALTER TABLE `Tournament` DROP COLUMN `seasonId`;
+DROP TABLE `Season`;
@@ -0,0 +1,14 @@
+-- Seasons group tournaments for per-season standings, the all-time `User` counters are left alone
+CREATE TABLE `Season` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `name` VARCHAR(191) NOT NULL,
+    `startsAt` DATETIME(3) NOT NULL,
+    `endsAt` DATETIME(3) NOT NULL,
+
+    UNIQUE INDEX `Season_name_key`(`name`),
+    PRIMARY KEY (`id`)
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
+
+ALTER TABLE `Tournament` ADD COLUMN `seasonId` INTEGER NULL;
+ALTER TABLE `Tournament` ADD INDEX `Tournament_seasonId_idx`(`seasonId`);
+ALTER TABLE `Tournament` ADD CONSTRAINT `Tournament_seasonId_fkey` FOREIGN KEY (`seasonId`) REFERENCES `Season`(`id`) ON DELETE SET NULL ON UPDATE CASCADE;
@@ -0,0 +1,15 @@
+-- Points each user got from each tournament, summing them gives `User.wins` & `User.ranking`
+CREATE TABLE `TournamentResult` (
+    `id` INTEGER NOT NULL AUTO_INCREMENT,
+    `tournamentId` INTEGER NOT NULL,
+    `userId` VARCHAR(191) NOT NULL,
+    `placement` INTEGER NULL,
+    `points` INTEGER NOT NULL,
+    `statPoints` INTEGER NOT NULL,
+
+    INDEX `TournamentResult_tournamentId_idx`(`tournamentId`),
+    INDEX `TournamentResult_userId_idx`(`userId`),
+    PRIMARY KEY (`id`),
+    CONSTRAINT `TournamentResult_tournamentId_fkey` FOREIGN KEY (`tournamentId`) REFERENCES `Tournament`(`id`) ON DELETE CASCADE ON UPDATE CASCADE,
+    CONSTRAINT `TournamentResult_userId_fkey` FOREIGN KEY (`userId`) REFERENCES `User`(`userId`) ON DELETE CASCADE ON UPDATE CASCADE
+) DEFAULT CHARACTER SET utf8mb4 COLLATE utf8mb4_unicode_ci;
@@ -0,0 +1,2 @@
+-- Name of the round format a tournament was uploaded with, null for tournaments from before it was stored
+ALTER TABLE `Tournament` ADD COLUMN `format` VARCHAR(191) NULL;
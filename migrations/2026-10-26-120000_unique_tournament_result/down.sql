@@ -0,0 +1 @@
+ALTER TABLE `TournamentResult` DROP INDEX `TournamentResult_tournamentId_userId_key`;
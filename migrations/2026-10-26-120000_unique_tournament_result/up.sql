@@ -0,0 +1,2 @@
+-- One result per user per tournament, so a tournament can't be applied twice
+ALTER TABLE `TournamentResult` ADD UNIQUE INDEX `TournamentResult_tournamentId_userId_key`(`tournamentId`, `userId`);
@@ -0,0 +1,187 @@
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::Write,
+    path::Path,
+};
+
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::config::Config;
+use crate::db::InsertSummary;
+use crate::error::CliError;
+use crate::output;
+
+/// One line of the audit log
+#[derive(Serialize, Debug)]
+pub struct AuditEntry {
+    pub timestamp: NaiveDateTime,
+    pub operator: String,
+    pub action: String,
+    pub profile: Option<String>,
+    pub database: String,
+    pub source: Option<String>,
+    pub source_sha256: Option<String>,
+    pub cli_version: String,
+    pub tournament_id: Option<i32>,
+    pub outcome: AuditOutcome,
+    /// Why the write failed, nothing of it was kept
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub rows: AuditRows,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOutcome {
+    Success,
+    Failed,
+}
+
+/// Rows written per table, tables that weren't touched are left out
+#[derive(Serialize, Debug, Default)]
+pub struct AuditRows {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tournaments: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matches: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_teams: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_users: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_user_stats: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_users: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub renamed_users: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_aliases: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_tournaments: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seasons: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tournament_users: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tournament_results: Option<usize>,
+}
+
+impl AuditEntry {
+    pub fn new(config: &Config, action: &str, database: &str) -> AuditEntry {
+        AuditEntry {
+            timestamp: Utc::now().naive_utc(),
+            operator: config.operator.to_owned(),
+            action: action.to_owned(),
+            profile: config.profile_name.to_owned(),
+            database: database.to_owned(),
+            source: None,
+            source_sha256: None,
+            cli_version: env!("CARGO_PKG_VERSION").to_owned(),
+            tournament_id: None,
+            outcome: AuditOutcome::Success,
+            error: None,
+            rows: AuditRows::default(),
+        }
+    }
+
+    /// Sets the source to a file and hashes its content, done before the write so the hash
+    /// is of what was read even if the write fails
+    pub fn source_file(mut self, path: &str) -> AuditEntry {
+        self.source = Some(path.to_owned());
+        self.source_sha256 = hash_file(path);
+        self
+    }
+
+    /// Sets the source to content that didn't come from a file, like an HTTP request body
+    pub fn source_content(mut self, source: &str, content: &[u8]) -> AuditEntry {
+        self.source = Some(source.to_owned());
+        self.source_sha256 = Some(hash(content));
+        self
+    }
+
+    pub fn failed(mut self, error: &CliError) -> AuditEntry {
+        self.outcome = AuditOutcome::Failed;
+        self.error = Some(error.message().to_owned());
+        self
+    }
+
+    pub fn with_summary(mut self, summary: &InsertSummary) -> AuditEntry {
+        self.tournament_id = Some(summary.tournament_id);
+        self.rows = AuditRows {
+            tournaments: Some(1),
+            matches: Some(summary.matches),
+            match_teams: Some(summary.match_teams),
+            match_users: Some(summary.match_users),
+            match_user_stats: Some(summary.match_user_stats),
+            users: Some(summary.users),
+            new_users: Some(summary.new_users),
+            renamed_users: Some(summary.renamed_users),
+            user_aliases: Some(summary.renamed_users),
+            next_tournaments: None,
+            seasons: None,
+            tournament_users: Some(summary.tournament_users),
+            tournament_results: Some(summary.tournament_results),
+        };
+        self
+    }
+}
+
+/// The configured operator name, falling back to the OS user
+pub fn default_operator() -> String {
+    env::var("USER")
+        .or_else(|_| env::var("USERNAME"))
+        .unwrap_or(String::from("unknown"))
+}
+
+/// Appends the entry to the audit log.
+/// The write already happened at this point so a failure here only warns.
+pub fn record(config: &Config, entry: &AuditEntry) {
+    if let Err(error) = append(&config.audit_log, entry) {
+        output::warning(
+            &format!("Failed to write audit log {}: ", config.audit_log.display()),
+            &error.to_string(),
+        );
+    }
+}
+
+/// Records the outcome of a tournament insert, `entry` is created with its source before
+/// the insert runs
+pub fn record_insert(
+    config: &Config,
+    entry: AuditEntry,
+    result: Result<InsertSummary, CliError>,
+) -> Result<InsertSummary, CliError> {
+    match &result {
+        Ok(summary) => record(config, &entry.with_summary(summary)),
+        Err(error) => record(config, &entry.failed(error)),
+    }
+
+    result
+}
+
+fn append(path: &Path, entry: &AuditEntry) -> std::io::Result<()> {
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)?;
+    }
+
+    let line = serde_json::to_string(entry)?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", line)
+}
+
+fn hash_file(path: &str) -> Option<String> {
+    let content = fs::read(path).ok()?;
+
+    Some(hash(&content))
+}
+
+fn hash(content: &[u8]) -> String {
+    let hash = Sha256::digest(content);
+
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
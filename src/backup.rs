@@ -0,0 +1,278 @@
+use std::fs;
+
+use chrono::{NaiveDateTime, Utc};
+use diesel::mysql::MysqlConnection;
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db;
+use crate::error::{CliError, DbContext};
+use crate::models::{
+    Match, MatchTeam, MatchUser, MatchUserStat, NextTournament, Season, Tournament,
+    TournamentResult, TournamentToUser, User, UserAlias,
+};
+
+const ARCHIVE_FORMAT_VERSION: i32 = 1;
+// Keeps single insert statements well below mysql's max_allowed_packet
+const INSERT_CHUNK_SIZE: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Archive {
+    pub format_version: i32,
+    pub cli_version: String,
+    pub created_at: NaiveDateTime,
+    // Archives from before seasons existed don't have this
+    #[serde(default)]
+    pub seasons: Vec<Season>,
+    pub tournaments: Vec<Tournament>,
+    pub matches: Vec<Match>,
+    // Archives from before teams existed don't have this
+    #[serde(default)]
+    pub match_teams: Vec<MatchTeam>,
+    pub match_users: Vec<MatchUser>,
+    // Archives from before stats existed don't have this
+    #[serde(default)]
+    pub match_user_stats: Vec<MatchUserStat>,
+    pub users: Vec<User>,
+    // Archives from before aliases existed don't have this
+    #[serde(default)]
+    pub user_aliases: Vec<UserAlias>,
+    pub next_tournaments: Vec<NextTournament>,
+    pub tournament_users: Vec<TournamentToUser>,
+    // Archives from before results were stored don't have this
+    #[serde(default)]
+    pub tournament_results: Vec<TournamentResult>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ArchiveCounts {
+    pub seasons: usize,
+    pub tournaments: usize,
+    pub matches: usize,
+    pub match_teams: usize,
+    pub match_users: usize,
+    pub match_user_stats: usize,
+    pub users: usize,
+    pub user_aliases: usize,
+    pub next_tournaments: usize,
+    pub tournament_users: usize,
+    pub tournament_results: usize,
+}
+
+impl Archive {
+    pub fn counts(&self) -> ArchiveCounts {
+        ArchiveCounts {
+            seasons: self.seasons.len(),
+            tournaments: self.tournaments.len(),
+            matches: self.matches.len(),
+            match_teams: self.match_teams.len(),
+            match_users: self.match_users.len(),
+            match_user_stats: self.match_user_stats.len(),
+            users: self.users.len(),
+            user_aliases: self.user_aliases.len(),
+            next_tournaments: self.next_tournaments.len(),
+            tournament_users: self.tournament_users.len(),
+            tournament_results: self.tournament_results.len(),
+        }
+    }
+}
+
+impl std::fmt::Display for ArchiveCounts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} Seasons, {} Tournaments, {} Matches, {} Match Teams, {} Match Users, {} Match User Stats, {} Users, {} User Aliases, {} Next Tournaments, {} Tournament Links, {} Tournament Results",
+            self.seasons,
+            self.tournaments,
+            self.matches,
+            self.match_teams,
+            self.match_users,
+            self.match_user_stats,
+            self.users,
+            self.user_aliases,
+            self.next_tournaments,
+            self.tournament_users,
+            self.tournament_results
+        )
+    }
+}
+
+pub fn dump(conn: &mut MysqlConnection) -> Result<Archive, CliError> {
+    use crate::schema::{
+        MatchTeam, MatchUser, MatchUserStat, Match_, NextTournament, Season, Tournament,
+        TournamentResult, User, UserAlias,
+    };
+
+    Ok(Archive {
+        format_version: ARCHIVE_FORMAT_VERSION,
+        cli_version: env!("CARGO_PKG_VERSION").to_owned(),
+        created_at: Utc::now().naive_utc(),
+        seasons: Season::table
+            .order(Season::id.asc())
+            .select(crate::models::Season::as_select())
+            .load(conn)
+            .db_context("Failed to dump seasons")?,
+        tournaments: Tournament::table
+            .order(Tournament::id.asc())
+            .select(crate::models::Tournament::as_select())
+            .load(conn)
+            .db_context("Failed to dump tournaments")?,
+        matches: Match_::table
+            .order(Match_::id.asc())
+            .select(crate::models::Match::as_select())
+            .load(conn)
+            .db_context("Failed to dump matches")?,
+        match_teams: MatchTeam::table
+            .order(MatchTeam::id.asc())
+            .select(crate::models::MatchTeam::as_select())
+            .load(conn)
+            .db_context("Failed to dump match teams")?,
+        match_users: MatchUser::table
+            .order(MatchUser::id.asc())
+            .select(crate::models::MatchUser::as_select())
+            .load(conn)
+            .db_context("Failed to dump match users")?,
+        match_user_stats: MatchUserStat::table
+            .order(MatchUserStat::id.asc())
+            .select(crate::models::MatchUserStat::as_select())
+            .load(conn)
+            .db_context("Failed to dump match user stats")?,
+        users: User::table
+            .order(User::userId.asc())
+            .select(crate::models::User::as_select())
+            .load(conn)
+            .db_context("Failed to dump users")?,
+        user_aliases: UserAlias::table
+            .order(UserAlias::id.asc())
+            .select(crate::models::UserAlias::as_select())
+            .load(conn)
+            .db_context("Failed to dump user aliases")?,
+        next_tournaments: NextTournament::table
+            .order(NextTournament::id.asc())
+            .select(crate::models::NextTournament::as_select())
+            .load(conn)
+            .db_context("Failed to dump next tournaments")?,
+        tournament_users: diesel::sql_query("SELECT A, B FROM _TournamentToUser ORDER BY A, B")
+            .load(conn)
+            .db_context("Failed to dump tournament to user links")?,
+        tournament_results: TournamentResult::table
+            .order(TournamentResult::id.asc())
+            .select(crate::models::TournamentResult::as_select())
+            .load(conn)
+            .db_context("Failed to dump tournament results")?,
+    })
+}
+
+/// Loads an archive into a database, refuses to touch a database that already has data
+pub fn restore(conn: &mut MysqlConnection, archive: &Archive) -> Result<(), CliError> {
+    use crate::schema::{
+        MatchTeam, MatchUser, MatchUserStat, Match_, NextTournament, Season, Tournament,
+        TournamentResult, User, UserAlias,
+    };
+
+    if archive.format_version != ARCHIVE_FORMAT_VERSION {
+        return Err(CliError::Parse(format!(
+            "Unsupported archive format version {} (expected {})",
+            archive.format_version, ARCHIVE_FORMAT_VERSION
+        )));
+    }
+
+    let existing: i64 = Tournament::table
+        .count()
+        .get_result::<i64>(conn)
+        .and_then(|t| Ok(t + Match_::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + MatchTeam::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + MatchUser::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + MatchUserStat::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + User::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + UserAlias::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + NextTournament::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + Season::table.count().get_result::<i64>(conn)?))
+        .and_then(|t| Ok(t + TournamentResult::table.count().get_result::<i64>(conn)?))
+        .db_context("Failed to check if database is empty")?;
+    if existing > 0 {
+        return Err(CliError::Database(format!(
+            "Database is not empty ({} rows), restore only works on an empty database",
+            existing
+        )));
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        for chunk in archive.users.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(User::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.user_aliases.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(UserAlias::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.seasons.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(Season::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.tournaments.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(Tournament::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.matches.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(Match_::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.match_teams.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(MatchTeam::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.match_users.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(MatchUser::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.match_user_stats.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(MatchUserStat::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.tournament_results.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(TournamentResult::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+        for chunk in archive.next_tournaments.chunks(INSERT_CHUNK_SIZE) {
+            diesel::insert_into(NextTournament::table)
+                .values(chunk)
+                .execute(conn)?;
+        }
+
+        let links: Vec<(i32, String)> = archive
+            .tournament_users
+            .iter()
+            .map(|link| (link.tournament_id, link.user_id.to_owned()))
+            .collect();
+        for chunk in links.chunks(INSERT_CHUNK_SIZE) {
+            db::create_tournament_user_link(conn, chunk.to_vec())?;
+        }
+
+        Ok(())
+    })
+    .db_context("Failed to restore archive, nothing was written")
+}
+
+pub fn write_archive(path: &str, archive: &Archive) -> Result<(), CliError> {
+    let json = serde_json::to_string_pretty(archive)
+        .map_err(|error| CliError::Io(format!("Failed to serialize archive, {}", error)))?;
+    fs::write(path, json)
+        .map_err(|error| CliError::Io(format!("Failed to write {}, {}", path, error)))
+}
+
+pub fn read_archive(path: &str) -> Result<Archive, CliError> {
+    let json = fs::read_to_string(path)
+        .map_err(|error| CliError::Io(format!("Failed to read {}, {}", path, error)))?;
+    serde_json::from_str(&json)
+        .map_err(|error| CliError::Parse(format!("Failed to parse {}, {}", path, error)))
+}
@@ -0,0 +1,412 @@
+use std::fmt::Write;
+
+use colored::Colorize;
+use serde::Serialize;
+
+use crate::output;
+use crate::rounds::Round;
+use crate::tournament_parser::{ImmuneStatus, LifeStatus, MatchUser, ParsedTournament};
+
+/// A broken progression rule, with the rounds & players involved
+#[derive(Serialize, Debug)]
+pub struct Violation {
+    pub message: String,
+    pub rounds: Vec<String>,
+    pub players: Vec<String>,
+}
+
+struct RoundPlayers<'a> {
+    round: &'a Round,
+    players: Vec<&'a MatchUser>,
+}
+
+/// Checks that players progress through the rounds of the tournament's format, in order:
+/// - everyone alive in a round plays the next one, except players who `may_skip_ahead`
+///   as long as they play a later round
+/// - nobody eliminated in an elimination round plays again, unless saved by their immunity
+/// - the final round's rank 1 is its only alive player
+pub fn check(tournament: &ParsedTournament) -> Vec<Violation> {
+    let rounds = round_players(tournament);
+
+    let mut violations: Vec<Violation> = vec![];
+    for (i, round) in rounds.iter().enumerate() {
+        let later = &rounds[i + 1..];
+        let Some(next) = later.first() else {
+            break;
+        };
+
+        let missing: Vec<String> = round
+            .players
+            .iter()
+            .filter(|p| p.life_status == LifeStatus::Alive && !plays(next, p))
+            .filter(|p| !(may_skip_ahead(round.round, p) && later.iter().any(|r| plays(r, p))))
+            .map(|p| p.username.to_owned())
+            .collect();
+        if !missing.is_empty() {
+            violations.push(Violation {
+                message: format!(
+                    "Alive in {} but missing from {}",
+                    round.round.name, next.round.name
+                ),
+                rounds: vec![round.round.name.to_owned(), next.round.name.to_owned()],
+                players: missing,
+            });
+        }
+
+        if !round.round.elimination {
+            continue;
+        }
+        for later_round in later {
+            let returned: Vec<String> = round
+                .players
+                .iter()
+                .filter(|p| {
+                    p.life_status == LifeStatus::Eliminated
+                        && p.immune_status != ImmuneStatus::Saved
+                })
+                .filter(|p| {
+                    later_round.players.iter().any(|again| {
+                        same_player(again, p) && again.immune_status != ImmuneStatus::Saved
+                    })
+                })
+                .map(|p| p.username.to_owned())
+                .collect();
+            if !returned.is_empty() {
+                violations.push(Violation {
+                    message: format!(
+                        "Eliminated in {} but playing again in {}",
+                        round.round.name, later_round.round.name
+                    ),
+                    rounds: vec![
+                        round.round.name.to_owned(),
+                        later_round.round.name.to_owned(),
+                    ],
+                    players: returned,
+                });
+            }
+        }
+    }
+
+    let final_round = tournament.format.final_round();
+    match rounds.last() {
+        Some(last) if Some(last.round) == final_round => {
+            let alive: Vec<&&MatchUser> = last
+                .players
+                .iter()
+                .filter(|p| p.life_status == LifeStatus::Alive)
+                .collect();
+            if alive.len() != 1 || alive[0].rank != 1 {
+                violations.push(Violation {
+                    message: format!(
+                        "{} should only have its rank 1 alive, found {} alive",
+                        last.round.name,
+                        alive.len()
+                    ),
+                    rounds: vec![last.round.name.to_owned()],
+                    players: alive.iter().map(|p| p.username.to_owned()).collect(),
+                });
+            }
+        }
+        _ => violations.push(Violation {
+            message: format!(
+                "There is no {}",
+                final_round
+                    .map(|r| r.name.as_str())
+                    .unwrap_or("final round")
+            ),
+            rounds: final_round.map(|r| r.name.to_owned()).into_iter().collect(),
+            players: vec![],
+        }),
+    }
+
+    violations
+}
+
+pub fn print(violations: &[Violation]) {
+    for violation in violations {
+        output::line(format!("{}{}", "  ! ".yellow(), violation.message.white()));
+        if !violation.players.is_empty() {
+            output::line(format!("    {}", violation.players.join(", ")).bright_black());
+        }
+    }
+}
+
+// Drawing sizes in px
+const COLUMN_WIDTH: usize = 220;
+const NODE_WIDTH: usize = 170;
+const NODE_HEIGHT: usize = 18;
+const ROW_HEIGHT: usize = 24;
+const MARGIN: usize = 20;
+const HEADER_HEIGHT: usize = 40;
+
+const ALIVE_FILL: &str = "#e8f5e9";
+const ELIMINATED_FILL: &str = "#eeeeee";
+const PLAYING_FILL: &str = "#fff8e1";
+const WINNER_FILL: &str = "#ffd54f";
+const IMMUNE_COLOUR: &str = "#268bd2";
+const SAVED_COLOUR: &str = "#2aa198";
+const BORDER_COLOUR: &str = "#9e9e9e";
+const EDGE_COLOUR: &str = "#c8c8c8";
+
+/// Every round with its players by rank, and the links between a player's consecutive rounds
+struct Drawing<'a> {
+    rounds: Vec<RoundPlayers<'a>>,
+    /// `(round, row)` of a player to `(round, row)` of their next round
+    edges: Vec<((usize, usize), (usize, usize))>,
+}
+
+impl Drawing<'_> {
+    fn new(tournament: &ParsedTournament) -> Drawing<'_> {
+        let mut rounds = round_players(tournament);
+        for round in &mut rounds {
+            round.players.sort_by_key(|p| p.rank);
+        }
+
+        let mut edges = vec![];
+        for (i, round) in rounds.iter().enumerate() {
+            for (row, player) in round.players.iter().enumerate() {
+                let next = rounds[i + 1..].iter().enumerate().find_map(|(j, later)| {
+                    later
+                        .players
+                        .iter()
+                        .position(|p| same_player(p, player))
+                        .map(|later_row| (i + 1 + j, later_row))
+                });
+                if let Some(next) = next {
+                    edges.push(((i, row), next));
+                }
+            }
+        }
+
+        Drawing { rounds, edges }
+    }
+
+    /// The rank 1 of the final round, when it's in the tournament
+    fn is_winner(&self, tournament: &ParsedTournament, round: usize, player: &MatchUser) -> bool {
+        Some(self.rounds[round].round) == tournament.format.final_round()
+            && player.rank == 1
+            && player.life_status == LifeStatus::Alive
+    }
+}
+
+/// Immune & saved players keep their colour on the way to their next round
+fn edge_colour(player: &MatchUser) -> &'static str {
+    match player.immune_status {
+        ImmuneStatus::Immune => IMMUNE_COLOUR,
+        ImmuneStatus::Saved => SAVED_COLOUR,
+        ImmuneStatus::None => EDGE_COLOUR,
+    }
+}
+
+struct NodeStyle {
+    fill: &'static str,
+    border: &'static str,
+    border_width: u8,
+    dashed: bool,
+}
+
+fn node_style(player: &MatchUser, winner: bool) -> NodeStyle {
+    let fill = match player.life_status {
+        _ if winner => WINNER_FILL,
+        LifeStatus::Alive => ALIVE_FILL,
+        LifeStatus::Eliminated => ELIMINATED_FILL,
+        LifeStatus::Playing => PLAYING_FILL,
+    };
+    let (border, border_width, dashed) = match player.immune_status {
+        ImmuneStatus::Immune => (IMMUNE_COLOUR, 2, false),
+        ImmuneStatus::Saved => (SAVED_COLOUR, 2, true),
+        ImmuneStatus::None => (BORDER_COLOUR, 1, false),
+    };
+
+    NodeStyle {
+        fill,
+        border,
+        border_width,
+        dashed,
+    }
+}
+
+/// Player progression as a Graphviz graph, one cluster per round from left to right
+pub fn dot(tournament: &ParsedTournament) -> String {
+    let drawing = Drawing::new(tournament);
+
+    let mut graph = String::from(
+        "digraph bracket {\n  rankdir=LR;\n  nodesep=0.05;\n  ranksep=1.5;\n  \
+         node [shape=box, style=\"rounded,filled\", fontname=\"Helvetica\", fontsize=10, height=0.2];\n  \
+         edge [arrowsize=0.4];\n",
+    );
+    for (i, round) in drawing.rounds.iter().enumerate() {
+        let _ = writeln!(graph, "  subgraph cluster_{} {{", i);
+        let _ = writeln!(graph, "    label=\"{}\";", dot_escape(&round.round.name));
+        for (row, player) in round.players.iter().enumerate() {
+            let style = node_style(player, drawing.is_winner(tournament, i, player));
+            let _ = writeln!(
+                graph,
+                "    r{}_{} [label=\"{}. {}\", fillcolor=\"{}\", color=\"{}\", penwidth={}{}];",
+                i,
+                row,
+                player.rank,
+                dot_escape(&player.username),
+                style.fill,
+                style.border,
+                style.border_width,
+                match style.dashed {
+                    true => ", style=\"rounded,filled,dashed\"",
+                    false => "",
+                }
+            );
+        }
+        graph.push_str("  }\n");
+    }
+    for ((from_round, from_row), (to_round, to_row)) in &drawing.edges {
+        let _ = writeln!(
+            graph,
+            "  r{}_{} -> r{}_{} [color=\"{}\"];",
+            from_round,
+            from_row,
+            to_round,
+            to_row,
+            edge_colour(drawing.rounds[*from_round].players[*from_row])
+        );
+    }
+    graph.push_str("}\n");
+
+    graph
+}
+
+/// Player progression as a standalone SVG, one column per round from left to right
+pub fn svg(tournament: &ParsedTournament) -> String {
+    let drawing = Drawing::new(tournament);
+    let rows = drawing
+        .rounds
+        .iter()
+        .map(|r| r.players.len())
+        .max()
+        .unwrap_or(0);
+    let width =
+        MARGIN * 2 + COLUMN_WIDTH * drawing.rounds.len().max(1) - (COLUMN_WIDTH - NODE_WIDTH);
+    let height = MARGIN * 2 + HEADER_HEIGHT + ROW_HEIGHT * rows;
+    let x = |round: usize| MARGIN + COLUMN_WIDTH * round;
+    let y = |row: usize| MARGIN + HEADER_HEIGHT + ROW_HEIGHT * row;
+
+    let mut image = String::new();
+    let _ = writeln!(
+        image,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\" font-family=\"Helvetica, Arial, sans-serif\" font-size=\"11\">",
+        width, height
+    );
+    let _ = writeln!(
+        image,
+        "<rect width=\"{}\" height=\"{}\" fill=\"#ffffff\"/>",
+        width, height
+    );
+
+    // Edges first so the boxes are drawn over them
+    for ((from_round, from_row), (to_round, to_row)) in &drawing.edges {
+        let (x1, y1) = (x(*from_round) + NODE_WIDTH, y(*from_row) + NODE_HEIGHT / 2);
+        let (x2, y2) = (x(*to_round), y(*to_row) + NODE_HEIGHT / 2);
+        let bend = (x2 - x1) / 2;
+        let _ = writeln!(
+            image,
+            "<path d=\"M{} {} C{} {} {} {} {} {}\" fill=\"none\" stroke=\"{}\"/>",
+            x1,
+            y1,
+            x1 + bend,
+            y1,
+            x2 - bend,
+            y2,
+            x2,
+            y2,
+            edge_colour(drawing.rounds[*from_round].players[*from_row])
+        );
+    }
+
+    for (i, round) in drawing.rounds.iter().enumerate() {
+        let _ = writeln!(
+            image,
+            "<text x=\"{}\" y=\"{}\" font-size=\"14\" font-weight=\"bold\">{}</text>",
+            x(i),
+            MARGIN + HEADER_HEIGHT / 2,
+            xml_escape(&round.round.name)
+        );
+        for (row, player) in round.players.iter().enumerate() {
+            let style = node_style(player, drawing.is_winner(tournament, i, player));
+            let _ = writeln!(
+                image,
+                "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" rx=\"4\" fill=\"{}\" stroke=\"{}\" stroke-width=\"{}\"{}/>",
+                x(i),
+                y(row),
+                NODE_WIDTH,
+                NODE_HEIGHT,
+                style.fill,
+                style.border,
+                style.border_width,
+                match style.dashed {
+                    true => " stroke-dasharray=\"4 2\"",
+                    false => "",
+                }
+            );
+            let _ = writeln!(
+                image,
+                "<text x=\"{}\" y=\"{}\" fill=\"{}\">{}. {}</text>",
+                x(i) + 5,
+                y(row) + NODE_HEIGHT - 5,
+                match player.life_status {
+                    LifeStatus::Eliminated => "#757575",
+                    _ => "#212121",
+                },
+                player.rank,
+                xml_escape(&player.username)
+            );
+        }
+    }
+    image.push_str("</svg>\n");
+
+    image
+}
+
+fn dot_escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// The players of every round in the tournament, in round order
+fn round_players(tournament: &ParsedTournament) -> Vec<RoundPlayers<'_>> {
+    let mut rounds: Vec<RoundPlayers> = vec![];
+    for m in &tournament.matches {
+        match rounds.iter_mut().find(|r| *r.round == m.match_type) {
+            Some(round) => round.players.extend(&m.players),
+            None => rounds.push(RoundPlayers {
+                round: &m.match_type,
+                players: m.players.iter().collect(),
+            }),
+        }
+    }
+    rounds.sort_by_key(|r| r.round.order);
+
+    rounds
+}
+
+/// By id, or by username for lines whose id hasn't been resolved yet
+fn same_player(a: &MatchUser, b: &MatchUser) -> bool {
+    match a.user_id.is_empty() || b.user_id.is_empty() {
+        true => a.username == b.username,
+        false => a.user_id == b.user_id,
+    }
+}
+
+/// Immune & saved players don't have to play the next round as long as they're in a later
+/// one, neither does the winner of a round with `winner_bye` in the format
+fn may_skip_ahead(round: &Round, player: &MatchUser) -> bool {
+    player.immune_status != ImmuneStatus::None || (round.winner_bye && player.rank == 1)
+}
+
+fn plays(round: &RoundPlayers, player: &MatchUser) -> bool {
+    round.players.iter().any(|p| same_player(p, player))
+}
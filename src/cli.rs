@@ -0,0 +1,300 @@
+use std::path::Path;
+
+use clap::{Args, CommandFactory, Parser, Subcommand};
+
+use crate::output::OutputMode;
+
+#[derive(Parser, Debug)]
+#[command(
+    name = "pbt-cli",
+    version,
+    about = "Uploads Party Bots Tournament files to PB Tournaments"
+)]
+pub struct Cli {
+    /// Path to a config file, instead of looking it up
+    #[arg(long, global = true)]
+    pub config: Option<String>,
+    /// Config profile to use, defaults to the config's `default_profile`
+    #[arg(long, global = true)]
+    pub profile: Option<String>,
+    /// Output format, `json` prints one JSON object per line
+    #[arg(long, global = true, value_enum, default_value_t = OutputMode::Human)]
+    pub output: OutputMode,
+
+    #[command(subcommand)]
+    pub command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Parse a tournament file and upload it
+    Upload(UploadArgs),
+    /// Keep uploading a tournament file as it's written, for live progress on the website
+    Live(LiveArgs),
+    /// Upload every completed tournament file that shows up in a folder
+    Watch {
+        /// Folder the tournament files are written to
+        dir: String,
+        /// Round format from the config, defaults to the profile's
+        #[arg(long)]
+        format: Option<String>,
+        /// Seconds between checks for new or changed files
+        #[arg(long, default_value_t = 5)]
+        interval: u64,
+    },
+    /// Serve an HTTP API for uploading, validating & reading tournaments
+    Serve {
+        /// Address to listen on
+        #[arg(long, default_value = "127.0.0.1:8080")]
+        bind: String,
+    },
+    /// Render a tournament into a self-contained HTML page & a Markdown summary
+    Report {
+        /// Tournament file, or the id of an uploaded tournament
+        source: String,
+        /// Folder the report files are written to
+        #[arg(long, default_value = ".")]
+        out: String,
+        /// Round format from the config, defaults to the profile's
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// Draw the player progression through the rounds as an SVG or Graphviz graph
+    Bracket {
+        /// Tournament file, or the id of an uploaded tournament
+        source: String,
+        /// Output file, `.svg` or `.dot`, defaults to `<title>.svg`
+        #[arg(long)]
+        out: Option<String>,
+        /// Round format from the config, defaults to the profile's
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// Parse a tournament file without uploading it
+    Validate {
+        /// Tournament file
+        file: String,
+        /// Round format from the config, defaults to the profile's
+        #[arg(long)]
+        format: Option<String>,
+    },
+    /// List the latest tournaments
+    List {
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+    },
+    /// Show the overall player rankings
+    Leaderboard {
+        #[arg(long, default_value_t = 20)]
+        limit: i64,
+        /// Rank by the total of a player stat (e.g. kills) instead of points
+        #[arg(long)]
+        stat: Option<String>,
+        /// Only count the tournaments of this season
+        #[arg(long)]
+        season: Option<String>,
+    },
+    /// Delete a tournament, taking its points & wins back off its players
+    Delete {
+        tournament_id: i32,
+        /// Delete without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Store every tournament's results again with the profile's scoring & reset the
+    /// players' wins and rankings to their totals.
+    ///
+    /// Manual changes to wins & ranking are replaced, and tournaments without stored results
+    /// get theirs worked out from their matches. Both are warned about before confirming
+    Recompute {
+        /// Recompute without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+    /// Copy a tournament from one profile's database to another's
+    Promote {
+        tournament_id: i32,
+        #[arg(long)]
+        from: String,
+        #[arg(long)]
+        to: String,
+    },
+    /// Dump all tournament data to a JSON archive
+    Backup {
+        /// Archive file to write
+        file: String,
+    },
+    /// Load a JSON archive into an empty database
+    Restore {
+        /// Archive file to read
+        file: String,
+    },
+    /// Look up & manage players
+    Player {
+        #[command(subcommand)]
+        command: PlayerCommand,
+    },
+    /// Create & list the seasons tournaments are grouped in
+    Season {
+        #[command(subcommand)]
+        command: SeasonCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PlayerCommand {
+    /// Find players by steam id, current or previous username
+    Find { name: String },
+    /// Merge a duplicate account into another, moving its matches, tournaments, wins & ranking
+    Merge {
+        /// Steam id of the user to keep
+        keep_id: String,
+        /// Steam id of the user to merge in & delete
+        drop_id: String,
+        /// Merge without asking for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SeasonCommand {
+    /// Create a season, existing tournaments in its dates without a season are added to it
+    Add {
+        name: String,
+        /// First day of the season (YYYY-MM-DD)
+        #[arg(long)]
+        start: String,
+        /// Last day of the season (YYYY-MM-DD)
+        #[arg(long)]
+        end: String,
+    },
+    /// List the seasons, latest first
+    List,
+}
+
+impl Command {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Command::Upload(_) => "upload",
+            Command::Live(_) => "live",
+            Command::Watch { .. } => "watch",
+            Command::Serve { .. } => "serve",
+            Command::Report { .. } => "report",
+            Command::Bracket { .. } => "bracket",
+            Command::Validate { .. } => "validate",
+            Command::List { .. } => "list",
+            Command::Leaderboard { .. } => "leaderboard",
+            Command::Delete { .. } => "delete",
+            Command::Recompute { .. } => "recompute",
+            Command::Promote { .. } => "promote",
+            Command::Backup { .. } => "backup",
+            Command::Restore { .. } => "restore",
+            Command::Player {
+                command: PlayerCommand::Find { .. },
+            } => "player find",
+            Command::Player {
+                command: PlayerCommand::Merge { .. },
+            } => "player merge",
+            Command::Season {
+                command: SeasonCommand::Add { .. },
+            } => "season add",
+            Command::Season {
+                command: SeasonCommand::List,
+            } => "season list",
+        }
+    }
+}
+
+#[derive(Args, Debug)]
+pub struct UploadArgs {
+    /// Tournament file
+    pub file: String,
+    /// Tournament title, skips all prompts when given
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Tournament date (YYYY-MM-DD HH:MM), defaults to now
+    #[arg(long)]
+    pub date: Option<String>,
+    /// Tournament media link
+    #[arg(long)]
+    pub link: Option<String>,
+    /// Upload without asking for confirmation after the preview
+    #[arg(long, short)]
+    pub yes: bool,
+    /// Round format from the config, defaults to the profile's
+    #[arg(long)]
+    pub format: Option<String>,
+    /// Season to add the tournament to, defaults to the season its date falls in
+    #[arg(long)]
+    pub season: Option<String>,
+    /// Amount of top placements shown in the preview
+    #[arg(long, default_value_t = 10)]
+    pub top: usize,
+}
+
+#[derive(Args, Debug)]
+pub struct LiveArgs {
+    /// Tournament file, uploaded again every time it changes
+    pub file: String,
+    /// Tournament title, skips all prompts when given
+    #[arg(long)]
+    pub title: Option<String>,
+    /// Tournament date (YYYY-MM-DD HH:MM), defaults to now
+    #[arg(long)]
+    pub date: Option<String>,
+    /// Tournament media link
+    #[arg(long)]
+    pub link: Option<String>,
+    /// Round format from the config, defaults to the profile's
+    #[arg(long)]
+    pub format: Option<String>,
+    /// Season to add the tournament to, defaults to the season its date falls in
+    #[arg(long)]
+    pub season: Option<String>,
+    /// Seconds between checks for changes
+    #[arg(long, default_value_t = 5)]
+    pub interval: u64,
+    /// Continue a live tournament created by an earlier run instead of creating a new one
+    #[arg(long)]
+    pub tournament: Option<i32>,
+}
+
+/// Keeps `pbt-cli <tournament file>` (and dropping a file on the binary) working
+/// by treating a leading argument that is an existing file and not a subcommand as
+/// `upload <file>`, anything else is left for clap to report
+pub fn with_default_subcommand(mut args: Vec<String>) -> Vec<String> {
+    let mut i = 1;
+    while let Some(arg) = args.get(i) {
+        match arg.as_str() {
+            // Global flags with a value in front of the subcommand
+            "--config" | "--profile" | "--output" => i += 2,
+            _ if arg.starts_with('-') && !arg.contains('=') => return args,
+            _ if arg.starts_with('-') => i += 1,
+            _ => {
+                if !is_subcommand(arg) && Path::new(arg).is_file() {
+                    args.insert(i, String::from("upload"));
+                }
+                return args;
+            }
+        }
+    }
+
+    args
+}
+
+/// A subcommand name or alias, including the `help` clap adds
+fn is_subcommand(name: &str) -> bool {
+    name == "help"
+        || Cli::command().get_subcommands().any(|command| {
+            command.get_name() == name || command.get_all_aliases().any(|a| a == name)
+        })
+}
+
+/// Whether JSON output was asked for, used when the arguments themselves fail to parse
+pub fn wants_json(args: &[String]) -> bool {
+    args.iter()
+        .zip(args.iter().skip(1))
+        .any(|(flag, value)| flag == "--output" && value == "json")
+        || args.iter().any(|arg| arg == "--output=json")
+}
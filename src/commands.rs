@@ -0,0 +1,1383 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use chrono::{Local, Utc};
+use colored::Colorize;
+use diesel::mysql::MysqlConnection;
+use serde_json::{json, Value};
+
+use crate::audit::{self, AuditEntry, AuditRows};
+use crate::backup;
+use crate::bracket;
+use crate::cli::{LiveArgs, UploadArgs};
+use crate::config::{self, Config, PromptDefaults, ScoringSystem};
+use crate::db;
+use crate::error::CliError;
+use crate::input::{self, UserInput};
+use crate::live;
+use crate::output::{self, step as cmd_step};
+use crate::preview;
+use crate::report;
+use crate::resolve;
+use crate::rounds::RoundFormat;
+use crate::season;
+use crate::steam_id;
+use crate::tournament_parser::{self, ParsedTournament};
+use crate::watch;
+use crate::webhook;
+
+const UPLOAD_STEPS: i32 = 6;
+const PROMOTE_STEPS: i32 = 6;
+const BACKUP_STEPS: i32 = 4;
+const RESTORE_STEPS: i32 = 4;
+
+pub fn print_config(config: &Config) {
+    if let Some(path) = &config.path {
+        output::line(
+            format!(
+                "Using config {} [{}]",
+                path.display(),
+                config.profile_name.as_deref().unwrap_or("no profile")
+            )
+            .bright_black()
+            .italic(),
+        );
+    }
+}
+
+/// Tournament title, date & link from the flags, prompting for them without `--title`
+fn tournament_input(
+    config: &Config,
+    title: &Option<String>,
+    date: &Option<String>,
+    link: &Option<String>,
+    season: &Option<String>,
+) -> Result<UserInput, CliError> {
+    let mut user_input = match title {
+        Some(title) => input::from_flags(
+            &config.defaults,
+            title.to_owned(),
+            date.as_deref(),
+            link.to_owned(),
+        ),
+        None if output::is_json() => Err(CliError::Usage(String::from(
+            "--title is required with --output json",
+        ))),
+        None => Ok(input::get_user_inputs(&config.defaults)),
+    }?;
+    user_input.season = season.to_owned();
+
+    Ok(user_input)
+}
+
+pub fn upload(args: &UploadArgs, config: &Config) -> Result<Value, CliError> {
+    let user_input = tournament_input(config, &args.title, &args.date, &args.link, &args.season)?;
+
+    cmd_step(
+        "Parsing Tournament File...".bright_black().italic(),
+        1,
+        UPLOAD_STEPS,
+    );
+    let format = config.formats.get(args.format.as_deref())?;
+    let mut parsed_tournament = tournament_parser::parse(&args.file, &format)?;
+    parsed_tournament.set_user_input(&user_input);
+    cmd_step("Finished Parsing File".green(), 2, UPLOAD_STEPS);
+
+    cmd_step(
+        "Connecting To MySql Database...".bright_black().italic(),
+        3,
+        UPLOAD_STEPS,
+    );
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+    cmd_step(
+        format!("Established MySql Database Connection [{}]", db_name).green(),
+        4,
+        UPLOAD_STEPS,
+    );
+    if let Some(name) = &parsed_tournament.season {
+        season::find(db_conn, name)?;
+    }
+
+    if !resolve::missing_usernames(&parsed_tournament).is_empty() {
+        let mut known = resolve::file_usernames(&parsed_tournament);
+        known.extend(db::get_all_usernames(db_conn)?);
+        let interactive = !args.yes && !output::is_json();
+        let (resolved, unresolved) =
+            resolve::resolve_missing_ids(&mut parsed_tournament, &known, interactive);
+        resolve::print_resolved(&resolved);
+        if !unresolved.is_empty() {
+            return Err(CliError::Parse(format!(
+                "Couldn't resolve missing Steam ids:\n{}",
+                resolve::describe_unresolved(&unresolved)
+            )));
+        }
+    }
+
+    let preview = preview::build(db_conn, &parsed_tournament, args.top)?;
+    preview::print(&preview);
+    if !args.yes {
+        if output::is_json() {
+            return Err(CliError::Usage(String::from(
+                "--yes is required with --output json",
+            )));
+        }
+        if !input::confirm("Upload this tournament?") {
+            return Err(CliError::Cancelled(String::from(
+                "Upload cancelled, nothing was written",
+            )));
+        }
+    }
+
+    cmd_step(
+        "Inserting Into DB...".bright_black().italic(),
+        5,
+        UPLOAD_STEPS,
+    );
+    let announcement = webhook::Announcement::new(&parsed_tournament);
+    let entry = AuditEntry::new(config, "upload", db_name).source_file(&args.file);
+    let summary = audit::record_insert(
+        config,
+        entry,
+        db::insert_parsed_tournament(
+            db_conn,
+            parsed_tournament,
+            &config.scoring,
+            &config.stat_points,
+        ),
+    )?;
+    cmd_step(
+        "Finished Inserting Tournament Into Database".green(),
+        6,
+        UPLOAD_STEPS,
+    );
+
+    webhook::announce(config, &announcement, summary.tournament_id);
+
+    output::line(
+        format!(
+            "\n[Finished Uploading Tournament To Server] [id {}]",
+            summary.tournament_id
+        )
+        .green()
+        .bold(),
+    );
+
+    Ok(json!(summary))
+}
+
+pub fn live(args: &LiveArgs, config: &Config) -> Result<Value, CliError> {
+    let user_input = tournament_input(config, &args.title, &args.date, &args.link, &args.season)?;
+    let format = config.formats.get(args.format.as_deref())?;
+    let path = Path::new(&args.file);
+
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+    output::line(format!("Established MySql Database Connection [{}]", db_name).green());
+    if let Some(id) = args.tournament {
+        if db::get_tournament(db_conn, id)?.is_none() {
+            return Err(CliError::Usage(format!("No tournament with id {}", id)));
+        }
+        db::ensure_not_finished(db_conn, id)?;
+    }
+    if let Some(name) = &args.season {
+        season::find(db_conn, name)?;
+    }
+
+    output::line(
+        format!(
+            "[Watching {} Until The {} Is Complete, Stop With Ctrl+C]",
+            args.file,
+            format
+                .final_round()
+                .map(|r| r.name.as_str())
+                .unwrap_or("Final")
+        )
+        .bright_black()
+        .italic(),
+    );
+
+    let mut live = LiveState {
+        tournament_id: args.tournament,
+        updates: 0,
+    };
+    let mut last_stamp = None;
+    loop {
+        let stamp = live::file_stamp(path)?;
+        if last_stamp != Some(stamp) {
+            last_stamp = Some(stamp);
+            if let Some(summary) = live_update(
+                db_conn,
+                db_name,
+                config,
+                &args.file,
+                &format,
+                &user_input,
+                &mut live,
+            )? {
+                output::line(
+                    format!(
+                        "\n[Tournament Complete, Finished Uploading To Server] [id {}]",
+                        summary.tournament_id
+                    )
+                    .green()
+                    .bold(),
+                );
+
+                return Ok(json!({ "updates": live.updates, "summary": summary }));
+            }
+        }
+
+        thread::sleep(Duration::from_secs(args.interval));
+    }
+}
+
+struct LiveState {
+    /// Created on the first update that can be written
+    tournament_id: Option<i32>,
+    updates: usize,
+}
+
+/// Writes the current state of a live tournament file, returns the summary once it's complete.
+/// Files that fail to parse or have unresolved players are skipped with a warning, since
+/// they're usually still being written
+fn live_update(
+    db_conn: &mut MysqlConnection,
+    db_name: &str,
+    config: &Config,
+    file: &str,
+    format: &RoundFormat,
+    user_input: &UserInput,
+    live: &mut LiveState,
+) -> Result<Option<db::InsertSummary>, CliError> {
+    let mut parsed_tournament = match tournament_parser::parse(file, format) {
+        Ok(parsed_tournament) => parsed_tournament,
+        Err(CliError::Parse(message)) => {
+            output::warning("[Skipped Update] ", &message);
+            return Ok(None);
+        }
+        Err(error) => return Err(error),
+    };
+    parsed_tournament.set_user_input(user_input);
+
+    if !resolve::missing_usernames(&parsed_tournament).is_empty() {
+        let mut known = resolve::file_usernames(&parsed_tournament);
+        known.extend(db::get_all_usernames(db_conn)?);
+        let (resolved, unresolved) =
+            resolve::resolve_missing_ids(&mut parsed_tournament, &known, false);
+        resolve::print_resolved(&resolved);
+        if !unresolved.is_empty() {
+            output::warning(
+                "[Skipped Update] ",
+                &format!(
+                    "Couldn't resolve missing Steam ids\n{}",
+                    resolve::describe_unresolved(&unresolved)
+                ),
+            );
+            return Ok(None);
+        }
+    }
+
+    let tournament_id = match live.tournament_id {
+        Some(id) => id,
+        None => {
+            let id = db::create_live_tournament(db_conn, &parsed_tournament)?;
+            output::line(format!("Created Live Tournament [id {}]", id).green());
+            live.tournament_id = Some(id);
+            id
+        }
+    };
+
+    if live::is_complete(&parsed_tournament) {
+        let entry = AuditEntry::new(config, "live", db_name).source_file(file);
+        let summary = audit::record_insert(
+            config,
+            entry,
+            db::finish_live_tournament(
+                db_conn,
+                tournament_id,
+                &parsed_tournament,
+                &config.scoring,
+                &config.stat_points,
+            ),
+        )?;
+        webhook::announce(
+            config,
+            &webhook::Announcement::new(&parsed_tournament),
+            tournament_id,
+        );
+        return Ok(Some(summary));
+    }
+
+    let summary = db::replace_live_matches(db_conn, tournament_id, &parsed_tournament)?;
+    let playing = live::playing_count(&parsed_tournament);
+    live.updates += 1;
+    output::event(
+        "live_update",
+        json!({
+            "tournament_id": tournament_id,
+            "matches": summary.matches,
+            "match_users": summary.match_users,
+            "playing": playing,
+        }),
+    );
+    output::line(format!(
+        "{}{}{}",
+        format!("[{}] ", Local::now().format("%H:%M:%S")).bright_black(),
+        format!("Updated {} Matches", summary.matches).white(),
+        format!(", {} Playing", playing).bright_black()
+    ));
+
+    // Nobody is playing in the final but it isn't complete, tell why instead of waiting silently
+    let final_round = parsed_tournament.format.final_round();
+    if playing == 0
+        && parsed_tournament
+            .matches
+            .iter()
+            .any(|m| Some(&m.match_type) == final_round)
+    {
+        output::warning(
+            "[Not Complete] ",
+            "The final has no players left playing but the bracket isn't consistent yet",
+        );
+        bracket::print(&bracket::check(&parsed_tournament));
+    }
+
+    Ok(None)
+}
+
+pub fn watch(
+    config: &Config,
+    dir: &str,
+    format: Option<&str>,
+    interval: u64,
+) -> Result<Value, CliError> {
+    let format = config.formats.get(format)?;
+    let dir = Path::new(dir);
+    if !dir.is_dir() {
+        return Err(CliError::Usage(format!(
+            "{} is not a folder",
+            dir.display()
+        )));
+    }
+
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+    output::line(format!("Established MySql Database Connection [{}]", db_name).green());
+    output::line(
+        format!(
+            "[Watching {} For Tournament Files, Stop With Ctrl+C]",
+            dir.display()
+        )
+        .bright_black()
+        .italic(),
+    );
+
+    // Files are only read once they haven't changed for a whole interval,
+    // and only read again after they change
+    let mut stamps: HashMap<PathBuf, live::FileStamp> = HashMap::new();
+    let mut checked: HashMap<PathBuf, live::FileStamp> = HashMap::new();
+    loop {
+        let files = watch::pending_files(dir)?;
+        stamps.retain(|path, _| files.contains(path));
+        checked.retain(|path, _| files.contains(path));
+
+        for path in files {
+            let Ok(stamp) = live::file_stamp(&path) else {
+                continue;
+            };
+            let previous = stamps.insert(path.clone(), stamp);
+            if previous != Some(stamp) || checked.get(&path) == Some(&stamp) {
+                continue;
+            }
+            checked.insert(path.clone(), stamp);
+
+            let file = path.display().to_string();
+            // Only the file's own problems move it to failed/, anything else may pass
+            let outcome = match watch_file(db_conn, db_name, config, &path, &format) {
+                Ok(outcome) => outcome,
+                Err(error @ (CliError::Parse(_) | CliError::Usage(_))) => {
+                    WatchOutcome::Failed(error.to_string())
+                }
+                Err(error) => {
+                    output::warning("[Retrying Later] ", error.message());
+                    checked.remove(&path);
+                    if matches!(error, CliError::Connection(_) | CliError::Database(_)) {
+                        reconnect(db_conn, config);
+                    }
+                    continue;
+                }
+            };
+
+            match outcome {
+                WatchOutcome::Waiting => {
+                    output::line(format!("{} isn't complete yet, waiting", file).bright_black())
+                }
+                WatchOutcome::Done(summary) => {
+                    let moved = match watch::move_to(&path, dir, watch::DONE_DIR_NAME) {
+                        Ok(moved) => moved,
+                        Err(error) => {
+                            output::warning(&format!("[Uploaded {}] ", file), error.message());
+                            continue;
+                        }
+                    };
+                    output::event(
+                        "uploaded",
+                        json!({ "file": file, "moved_to": moved, "summary": summary }),
+                    );
+                    output::line(
+                        format!(
+                            "[Uploaded {}] [id {}] -> {}",
+                            file,
+                            summary.tournament_id,
+                            moved.display()
+                        )
+                        .green()
+                        .bold(),
+                    );
+                }
+                WatchOutcome::Failed(reason) => {
+                    let moved = match watch::move_to(&path, dir, watch::FAILED_DIR_NAME) {
+                        Ok(moved) => moved,
+                        Err(error) => {
+                            output::warning(
+                                &format!("[Failed {}] ", file),
+                                &format!("{}\n{}", reason, error.message()),
+                            );
+                            continue;
+                        }
+                    };
+                    if let Err(error) = watch::write_reason(&moved, &reason) {
+                        output::warning("[Failed To Write Reason] ", error.message());
+                    }
+                    output::event(
+                        "failed",
+                        json!({ "file": file, "moved_to": moved, "reason": reason }),
+                    );
+                    output::warning(
+                        &format!("[Failed {}] ", file),
+                        &format!("{}\nMoved to {}", reason, moved.display()),
+                    );
+                }
+            }
+        }
+
+        thread::sleep(Duration::from_secs(interval));
+    }
+}
+
+/// Swaps a connection that may have dropped for a new one, keeping the old one if the
+/// database still can't be reached so the next attempt tries again
+fn reconnect(db_conn: &mut MysqlConnection, config: &Config) {
+    match db::establish_connection(&config.database_url, &config.retry_policy) {
+        Ok((conn, _)) => *db_conn = conn,
+        Err(error) => output::warning("[Failed To Reconnect] ", error.message()),
+    }
+}
+
+enum WatchOutcome {
+    /// Valid so far, but the final isn't complete yet
+    Waiting,
+    Done(db::InsertSummary),
+    Failed(String),
+}
+
+/// Uploads a complete tournament file, taking the title, date & link from its header
+/// with the profile's defaults (or the file name) for anything missing
+fn watch_file(
+    db_conn: &mut MysqlConnection,
+    db_name: &str,
+    config: &Config,
+    path: &Path,
+    format: &RoundFormat,
+) -> Result<WatchOutcome, CliError> {
+    let file = path.display().to_string();
+    let mut parsed_tournament = tournament_parser::parse(&file, format)?;
+    if !live::is_complete(&parsed_tournament) {
+        return Ok(WatchOutcome::Waiting);
+    }
+
+    let user_input = header_input(&config.defaults, &parsed_tournament, path);
+    parsed_tournament.set_user_input(&user_input);
+
+    if !resolve::missing_usernames(&parsed_tournament).is_empty() {
+        let mut known = resolve::file_usernames(&parsed_tournament);
+        known.extend(db::get_all_usernames(db_conn)?);
+        let (resolved, unresolved) =
+            resolve::resolve_missing_ids(&mut parsed_tournament, &known, false);
+        resolve::print_resolved(&resolved);
+        if !unresolved.is_empty() {
+            return Ok(WatchOutcome::Failed(format!(
+                "Couldn't resolve missing Steam ids:\n{}",
+                resolve::describe_unresolved(&unresolved)
+            )));
+        }
+    }
+
+    if db::tournament_exists(db_conn, &parsed_tournament.title, parsed_tournament.date)? {
+        return Ok(WatchOutcome::Failed(format!(
+            "A tournament \"{}\" on {} already exists",
+            parsed_tournament.title, parsed_tournament.date
+        )));
+    }
+
+    let announcement = webhook::Announcement::new(&parsed_tournament);
+    let entry = AuditEntry::new(config, "watch", db_name).source_file(&file);
+    let summary = audit::record_insert(
+        config,
+        entry,
+        db::insert_parsed_tournament(
+            db_conn,
+            parsed_tournament,
+            &config.scoring,
+            &config.stat_points,
+        ),
+    )?;
+    webhook::announce(config, &announcement, summary.tournament_id);
+
+    Ok(WatchOutcome::Done(summary))
+}
+
+fn header_input(
+    defaults: &PromptDefaults,
+    parsed_tournament: &ParsedTournament,
+    path: &Path,
+) -> UserInput {
+    let header = &parsed_tournament.header;
+    UserInput {
+        title: header
+            .title
+            .to_owned()
+            .or(defaults.title.to_owned())
+            .unwrap_or_else(|| {
+                path.file_stem()
+                    .map(|stem| stem.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            }),
+        date: header.date.unwrap_or_else(|| Utc::now().naive_local()),
+        link: header.link.to_owned().or(defaults.link.to_owned()),
+        season: header.season.to_owned(),
+    }
+}
+
+/// Writes `<title>.html` & `<title>.md` into `out`
+pub fn report(
+    config_path: Option<&str>,
+    profile_name: Option<&str>,
+    source: &str,
+    out: &str,
+    format: Option<&str>,
+) -> Result<Value, CliError> {
+    let (tournament, scoring, stat_points) =
+        load_source(config_path, profile_name, source, format)?;
+
+    let standings = report::standings(&tournament, &scoring, &stat_points);
+    let out_dir = Path::new(out);
+    fs::create_dir_all(out_dir)
+        .map_err(|error| CliError::Io(format!("Failed to create {}, {}", out, error)))?;
+
+    let stem = report::file_stem(&tournament.title);
+    let html_path = out_dir.join(format!("{}.html", stem));
+    let markdown_path = out_dir.join(format!("{}.md", stem));
+    for (path, content) in [
+        (&html_path, report::html(&tournament, &standings)),
+        (&markdown_path, report::markdown(&tournament, &standings)),
+    ] {
+        fs::write(path, content).map_err(|error| {
+            CliError::Io(format!("Failed to write {}, {}", path.display(), error))
+        })?;
+        output::line(format!("{}{}", "◆ Wrote ".green(), path.display()));
+    }
+
+    Ok(json!({
+        "html": html_path,
+        "markdown": markdown_path,
+        "standings": standings,
+    }))
+}
+
+/// Draws the player progression through the rounds as an SVG, or a Graphviz graph when `out`
+/// ends in `.dot`. Defaults to `<title>.svg`
+pub fn bracket(
+    config_path: Option<&str>,
+    profile_name: Option<&str>,
+    source: &str,
+    out: Option<&str>,
+    format: Option<&str>,
+) -> Result<Value, CliError> {
+    let (tournament, _, _) = load_source(config_path, profile_name, source, format)?;
+
+    let path = match out {
+        Some(out) => PathBuf::from(out),
+        None => PathBuf::from(format!("{}.svg", report::file_stem(&tournament.title))),
+    };
+    let content = match path.extension().and_then(|ext| ext.to_str()) {
+        Some("svg") => bracket::svg(&tournament),
+        Some("dot") | Some("gv") => bracket::dot(&tournament),
+        _ => {
+            return Err(CliError::Usage(format!(
+                "Can't tell the output type of {}, use a .svg or .dot file",
+                path.display()
+            )))
+        }
+    };
+    fs::write(&path, content)
+        .map_err(|error| CliError::Io(format!("Failed to write {}, {}", path.display(), error)))?;
+    output::line(format!("{}{}", "◆ Wrote ".green(), path.display()));
+
+    Ok(json!({ "file": path }))
+}
+
+/// A tournament from a file, or from the database when `source` is the id of an uploaded
+/// tournament and no such file exists. Comes with the profile's scoring
+fn load_source(
+    config_path: Option<&str>,
+    profile_name: Option<&str>,
+    source: &str,
+    format: Option<&str>,
+) -> Result<(ParsedTournament, ScoringSystem, HashMap<String, i32>), CliError> {
+    Ok(match source.parse::<i32>() {
+        Ok(tournament_id) if !Path::new(source).exists() => {
+            let config = config::load(config_path, profile_name)?;
+            print_config(&config);
+            let (db_conn, _) =
+                &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+            let tournament =
+                db::get_parsed_tournament(db_conn, tournament_id, &config.formats, format)?
+                    .ok_or_else(|| {
+                        CliError::Usage(format!("No tournament with id {}", tournament_id))
+                    })?;
+
+            (tournament, config.scoring, config.stat_points)
+        }
+        _ => {
+            let (profile, formats) = config::load_profile(config_path, profile_name)?;
+            let mut tournament = tournament_parser::parse(source, &formats.get(format)?)?;
+            let user_input = header_input(&profile.defaults, &tournament, Path::new(source));
+            tournament.set_user_input(&user_input);
+
+            (tournament, profile.scoring, profile.stat_points)
+        }
+    })
+}
+
+pub fn validate(file: &str, format: &RoundFormat) -> Result<Value, CliError> {
+    let mut parsed_tournament = tournament_parser::parse(file, format)?;
+    let known = resolve::file_usernames(&parsed_tournament);
+    let (resolved, unresolved) =
+        resolve::resolve_missing_ids(&mut parsed_tournament, &known, false);
+    let overall = tournament_parser::get_overall_player_list(&parsed_tournament);
+
+    let violations = bracket::check(&parsed_tournament);
+    if !violations.is_empty() {
+        return Err(CliError::Parse(format!(
+            "Bracket progression is inconsistent:\n{}",
+            violations
+                .iter()
+                .map(|v| format!("{} ({})", v.message, v.players.join(", ")))
+                .collect::<Vec<String>>()
+                .join("\n")
+        )));
+    }
+
+    output::line(
+        format!("[{} Is A Valid Tournament File]", file)
+            .green()
+            .bold(),
+    );
+    for m in &parsed_tournament.matches {
+        output::line(format!(
+            "{}{}{}",
+            "◆ ".green(),
+            match m.teams.len() {
+                0 => format!("{}: {} Players", m.match_type.name, m.players.len()),
+                teams => format!(
+                    "{}: {} Teams, {} Players",
+                    m.match_type.name,
+                    teams,
+                    m.players.len()
+                ),
+            }
+            .white(),
+            m.info
+                .map
+                .as_ref()
+                .map(|map| format!(" [{}]", map))
+                .unwrap_or_default()
+                .bright_black()
+        ));
+    }
+    output::line(
+        format!(
+            "◆ Version {}, {} Placed Players",
+            parsed_tournament.version,
+            overall.len()
+        )
+        .bright_black()
+        .italic(),
+    );
+    let stat_names = parsed_tournament.stat_names();
+    if !stat_names.is_empty() {
+        output::line(
+            format!("◆ Stats: {}", stat_names.join(", "))
+                .bright_black()
+                .italic(),
+        );
+    }
+
+    resolve::print_resolved(&resolved);
+    if !unresolved.is_empty() {
+        output::warning(
+            "[Players Without Steam Id] ",
+            &format!(
+                "Resolved against the database on upload\n{}",
+                resolve::describe_unresolved(&unresolved)
+            ),
+        );
+    }
+
+    let matches: Vec<Value> = parsed_tournament
+        .matches
+        .iter()
+        .map(|m| {
+            json!({
+                "match_type": m.match_type.key,
+                "teams": m.teams.len(),
+                "players": m.players.len(),
+                "map": m.info.map,
+                "mode": m.info.mode,
+                "started_at": m.info.started_at,
+                "duration_secs": m.info.duration_secs,
+            })
+        })
+        .collect();
+    Ok(json!({
+        "version": parsed_tournament.version,
+        "matches": matches,
+        "placed_players": overall.len(),
+        "stats": stat_names,
+        "resolved_players": resolved,
+        "unresolved_players": unresolved,
+    }))
+}
+
+pub fn list(config: &Config, limit: i64) -> Result<Value, CliError> {
+    let (db_conn, _) = &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let tournaments = db::list_tournaments(db_conn, limit)?;
+    for tournament in &tournaments {
+        output::line(format!(
+            "{}{}{}",
+            format!("[{}] ", tournament.id).bright_black(),
+            tournament.title.white().bold(),
+            format!(
+                " {} v{}{}",
+                tournament.date.format("%Y-%m-%d %H:%M"),
+                tournament.version,
+                tournament
+                    .link
+                    .as_ref()
+                    .map(|link| format!(" {}", link))
+                    .unwrap_or_default()
+            )
+            .bright_black()
+        ));
+    }
+
+    Ok(json!({ "tournaments": tournaments }))
+}
+
+pub fn leaderboard(
+    config: &Config,
+    limit: i64,
+    stat: Option<&str>,
+    season_name: Option<&str>,
+) -> Result<Value, CliError> {
+    let (db_conn, _) = &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let season = match season_name {
+        Some(name) => Some(season::find(db_conn, name)?),
+        None => None,
+    };
+    if let Some(season) = &season {
+        output::line(format!(
+            "{}{}\n",
+            season.name.white().bold(),
+            format!(" {}", season::format_range(season.startsAt, season.endsAt)).bright_black()
+        ));
+    }
+
+    if let Some(stat) = stat {
+        let stat = stat.to_lowercase();
+        let season_id = season.as_ref().map(|season| season.id);
+        let totals = db::get_stat_leaderboard(db_conn, &stat, season_id, limit)?;
+        if totals.is_empty() {
+            output::line(format!("No players have a '{}' stat", stat).yellow());
+        }
+        for (i, total) in totals.iter().enumerate() {
+            output::line(format!(
+                "{}{}{}",
+                format!("{:>3}. ", i + 1).bright_black(),
+                total.username.white().bold(),
+                format!(" {} {}", total.total, stat).bright_black()
+            ));
+        }
+
+        return Ok(json!({ "stat": stat, "season": season, "users": totals }));
+    }
+
+    if let Some(season) = season {
+        let standings = db::get_season_leaderboard(db_conn, season.id, limit)?;
+        if standings.is_empty() {
+            output::line("No finished tournaments in this season yet".yellow());
+        }
+        for (i, standing) in standings.iter().enumerate() {
+            output::line(format!(
+                "{}{}{}",
+                format!("{:>3}. ", i + 1).bright_black(),
+                standing.username.white().bold(),
+                format!(
+                    " {} Points, {} Wins, {} Tournaments",
+                    standing.points, standing.wins, standing.tournaments
+                )
+                .bright_black()
+            ));
+        }
+
+        return Ok(json!({ "season": season, "users": standings }));
+    }
+
+    let users = db::get_leaderboard(db_conn, limit)?;
+    for (i, user) in users.iter().enumerate() {
+        output::line(format!(
+            "{}{}{}",
+            format!("{:>3}. ", i + 1).bright_black(),
+            user.username.white().bold(),
+            format!(" {} Points, {} Wins", user.ranking, user.wins).bright_black()
+        ));
+    }
+
+    Ok(json!({ "users": users }))
+}
+
+pub fn add_season(config: &Config, name: &str, start: &str, end: &str) -> Result<Value, CliError> {
+    let new_season = season::new_season(name, start, end)?;
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let (season, tournaments) = db::create_season(db_conn, new_season)?;
+
+    let mut entry = AuditEntry::new(config, "season add", db_name);
+    entry.source = Some(season.name.to_owned());
+    entry.rows = AuditRows {
+        seasons: Some(1),
+        tournaments: Some(tournaments),
+        ..AuditRows::default()
+    };
+    audit::record(config, &entry);
+
+    output::line(
+        format!(
+            "[Created Season {} ({})] [{} Existing Tournaments Added]",
+            season.name,
+            season::format_range(season.startsAt, season.endsAt),
+            tournaments
+        )
+        .green()
+        .bold(),
+    );
+
+    Ok(json!({ "season": season, "tournaments": tournaments }))
+}
+
+pub fn list_seasons(config: &Config) -> Result<Value, CliError> {
+    let (db_conn, _) = &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let seasons = db::get_seasons(db_conn)?;
+    if seasons.is_empty() {
+        output::line("No seasons yet, create one with `pbt-cli season add`".yellow());
+    }
+
+    let mut listed: Vec<Value> = vec![];
+    for season in seasons {
+        let tournaments = db::get_season_tournament_ids(db_conn, season.id)?.len();
+        output::line(format!(
+            "{}{}{}",
+            format!("[{}] ", season.id).bright_black(),
+            season.name.white().bold(),
+            format!(
+                " {}, {} Tournaments",
+                season::format_range(season.startsAt, season.endsAt),
+                tournaments
+            )
+            .bright_black()
+        ));
+
+        listed.push(json!({ "season": season, "tournaments": tournaments }));
+    }
+
+    Ok(json!({ "seasons": listed }))
+}
+
+pub fn find_player(config: &Config, name: &str) -> Result<Value, CliError> {
+    let (db_conn, _) = &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    // Ids may be looked up in any form, names are matched as typed
+    let name = &steam_id::normalise(name).unwrap_or_else(|_| name.to_owned());
+    let found = db::find_users(db_conn, name)?;
+    if found.is_empty() {
+        output::line(format!("No players matching \"{}\"", name).yellow());
+    }
+
+    let mut players: Vec<Value> = vec![];
+    for (user, aliases) in found {
+        output::line(format!(
+            "{}{}{}",
+            user.username.white().bold(),
+            format!(" [{}]", user.userId).bright_black(),
+            format!(" {} Points, {} Wins", user.ranking, user.wins).bright_black()
+        ));
+        for alias in &aliases {
+            output::line(format!(
+                "{}{}{}",
+                "  formerly ".bright_black(),
+                alias.username.white(),
+                format!(" (until {})", alias.changedAt.format("%Y-%m-%d")).bright_black()
+            ));
+        }
+
+        players.push(json!({ "user": user, "aliases": aliases }));
+    }
+
+    Ok(json!({ "players": players }))
+}
+
+pub fn merge_players(
+    config: &Config,
+    keep_id: &str,
+    drop_id: &str,
+    yes: bool,
+) -> Result<Value, CliError> {
+    let keep_id = steam_id::normalise(keep_id).map_err(CliError::Usage)?;
+    let drop_id = steam_id::normalise(drop_id).map_err(CliError::Usage)?;
+    let (keep_id, drop_id) = (keep_id.as_str(), drop_id.as_str());
+    if keep_id == drop_id {
+        return Err(CliError::Usage(String::from(
+            "Can't merge a player into itself",
+        )));
+    }
+
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let users = db::get_users(db_conn, &[keep_id.to_owned(), drop_id.to_owned()])?;
+    for (label, id) in [("Keep", keep_id), ("Drop", drop_id)] {
+        let user = users
+            .iter()
+            .find(|u| u.userId == id)
+            .ok_or_else(|| CliError::Usage(format!("No user with id {}", id)))?;
+        output::line(format!(
+            "{}{}{}",
+            format!("◆ {}: ", label).green(),
+            user.username.white().bold(),
+            format!(
+                " [{}] {} Points, {} Wins",
+                user.userId, user.ranking, user.wins
+            )
+            .bright_black()
+        ));
+    }
+    db::ensure_no_shared_tournaments(db_conn, keep_id, drop_id)?;
+
+    if !yes {
+        if output::is_json() {
+            return Err(CliError::Usage(String::from(
+                "--yes is required with --output json",
+            )));
+        }
+        if !input::confirm("Merge these players?") {
+            return Err(CliError::Cancelled(String::from(
+                "Merge cancelled, nothing was changed",
+            )));
+        }
+    }
+
+    let summary = db::merge_users(db_conn, keep_id, drop_id)?;
+
+    let mut entry = AuditEntry::new(config, "merge", db_name);
+    entry.source = Some(format!("{} -> {}", drop_id, keep_id));
+    entry.rows = AuditRows {
+        match_users: Some(summary.match_users),
+        users: Some(2),
+        user_aliases: Some(summary.user_aliases),
+        tournament_users: Some(summary.tournament_users),
+        tournament_results: Some(summary.tournament_results),
+        ..AuditRows::default()
+    };
+    audit::record(config, &entry);
+
+    output::line(
+        format!("\n[Merged {} Into {}]", drop_id, keep_id)
+            .green()
+            .bold(),
+    );
+
+    Ok(json!({ "keep_id": keep_id, "drop_id": drop_id, "rows": summary }))
+}
+
+/// Deletes a tournament & takes its stored results back off the users' wins & rankings
+pub fn delete_tournament(
+    config: &Config,
+    tournament_id: i32,
+    yes: bool,
+) -> Result<Value, CliError> {
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let tournament = db::get_tournament(db_conn, tournament_id)?
+        .ok_or_else(|| CliError::Usage(format!("No tournament with id {}", tournament_id)))?;
+    db::ensure_results_stored(db_conn, tournament_id)?;
+    let results = db::get_tournament_results(db_conn, tournament_id)?;
+
+    output::line(format!(
+        "{}{}{}",
+        format!("[{}] ", tournament.id).bright_black(),
+        tournament.title.white().bold(),
+        format!(" {}", tournament.date.format("%Y-%m-%d %H:%M")).bright_black()
+    ));
+    output::line(format!(
+        "{}{}",
+        "◆ Takes Off: ".green(),
+        format!(
+            "{} Points & {} Wins From {} Players",
+            results.iter().map(|r| r.points + r.statPoints).sum::<i32>(),
+            results.iter().filter(|r| r.placement == Some(1)).count(),
+            results.len()
+        )
+        .white()
+    ));
+
+    if !yes {
+        if output::is_json() {
+            return Err(CliError::Usage(String::from(
+                "--yes is required with --output json",
+            )));
+        }
+        if !input::confirm("Delete this tournament?") {
+            return Err(CliError::Cancelled(String::from(
+                "Delete cancelled, nothing was changed",
+            )));
+        }
+    }
+
+    let summary = db::delete_tournament(db_conn, tournament_id)?;
+
+    let mut entry = AuditEntry::new(config, "delete", db_name);
+    entry.tournament_id = Some(tournament_id);
+    entry.rows = AuditRows {
+        tournaments: Some(1),
+        matches: Some(summary.matches),
+        users: Some(summary.tournament_results),
+        tournament_users: Some(summary.tournament_users),
+        tournament_results: Some(summary.tournament_results),
+        ..AuditRows::default()
+    };
+    audit::record(config, &entry);
+
+    output::line(
+        format!(
+            "\n[Deleted Tournament \"{}\"] [id {}]",
+            tournament.title, tournament_id
+        )
+        .green()
+        .bold(),
+    );
+
+    Ok(json!({ "tournament_id": tournament_id, "rows": summary }))
+}
+
+/// Stores the results of every finished tournament again with the profile's scoring and
+/// resets the users' wins & rankings to their totals
+pub fn recompute(config: &Config, yes: bool) -> Result<Value, CliError> {
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+
+    let mut tournaments: Vec<(i32, ParsedTournament)> = vec![];
+    for tournament_id in db::get_finished_tournament_ids(db_conn)? {
+        if let Some(parsed) =
+            db::get_parsed_tournament(db_conn, tournament_id, &config.formats, None)?
+        {
+            tournaments.push((tournament_id, parsed));
+        }
+    }
+    output::line(format!(
+        "{}{}",
+        "◆ Finished Tournaments: ".green(),
+        tournaments.len().to_string().white()
+    ));
+
+    // Anything the stored results don't account for is lost, so it's pointed out first
+    let without_results = db::get_tournament_ids_without_results(db_conn)?;
+    if !without_results.is_empty() {
+        output::warning(
+            "[Tournaments Without Stored Results] ",
+            &format!(
+                "{} will be worked out from their matches with the current scoring, the points \
+                 they gave before may differ",
+                without_results
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        );
+    }
+    let users_off = db::count_users_off_results(db_conn)?;
+    if users_off > 0 {
+        output::warning(
+            "[Totals Will Be Replaced] ",
+            &format!(
+                "{} players' wins or ranking don't match their stored results \
+                 (manual changes or tournaments without results), they're reset to the totals",
+                users_off
+            ),
+        );
+    }
+
+    if !yes {
+        if output::is_json() {
+            return Err(CliError::Usage(String::from(
+                "--yes is required with --output json",
+            )));
+        }
+        if !input::confirm("Recompute their results & reset every player's wins and ranking?") {
+            return Err(CliError::Cancelled(String::from(
+                "Recompute cancelled, nothing was changed",
+            )));
+        }
+    }
+
+    let summary =
+        db::recompute_results(db_conn, &tournaments, &config.scoring, &config.stat_points)?;
+
+    let mut entry = AuditEntry::new(config, "recompute", db_name);
+    entry.rows = AuditRows {
+        users: Some(summary.users),
+        tournament_results: Some(summary.tournament_results),
+        ..AuditRows::default()
+    };
+    audit::record(config, &entry);
+
+    output::line(
+        format!(
+            "\n[Recomputed {} Results Of {} Tournaments]",
+            summary.tournament_results, summary.tournaments
+        )
+        .green()
+        .bold(),
+    );
+
+    Ok(json!({
+        "rows": summary,
+        "tournaments_without_results": without_results,
+        "users_reset": users_off,
+    }))
+}
+
+/// Copies a tournament from one profile's database to another's,
+/// re-applying the user wins & rankings with the target profile's scoring
+pub fn promote(
+    config_path: Option<&str>,
+    tournament_id: i32,
+    from: &str,
+    to: &str,
+) -> Result<Value, CliError> {
+    let from_config = config::load(config_path, Some(from))?;
+    let to_config = config::load(config_path, Some(to))?;
+    print_config(&from_config);
+    output::line("");
+
+    cmd_step(
+        format!("Connecting To '{}' Database...", from)
+            .bright_black()
+            .italic(),
+        1,
+        PROMOTE_STEPS,
+    );
+    let (from_conn, from_db_name) =
+        &mut db::establish_connection(&from_config.database_url, &from_config.retry_policy)?;
+    cmd_step(
+        format!(
+            "Established '{}' Database Connection [{}]",
+            from, from_db_name
+        )
+        .green(),
+        2,
+        PROMOTE_STEPS,
+    );
+
+    let parsed_tournament =
+        db::get_parsed_tournament(from_conn, tournament_id, &from_config.formats, None)?
+            .ok_or_else(|| {
+                CliError::Usage(format!(
+                    "No tournament with id {} in '{}'",
+                    tournament_id, from
+                ))
+            })?;
+    cmd_step(
+        format!(
+            "Read Tournament \"{}\" ({} Matches)",
+            parsed_tournament.title,
+            parsed_tournament.matches.len()
+        )
+        .green(),
+        3,
+        PROMOTE_STEPS,
+    );
+
+    cmd_step(
+        format!("Connecting To '{}' Database...", to)
+            .bright_black()
+            .italic(),
+        4,
+        PROMOTE_STEPS,
+    );
+    let (to_conn, to_db_name) =
+        &mut db::establish_connection(&to_config.database_url, &to_config.retry_policy)?;
+    cmd_step(
+        format!("Established '{}' Database Connection [{}]", to, to_db_name).green(),
+        5,
+        PROMOTE_STEPS,
+    );
+
+    if db::tournament_exists(to_conn, &parsed_tournament.title, parsed_tournament.date)? {
+        return Err(CliError::Usage(format!(
+            "Tournament \"{}\" ({}) already exists in '{}'",
+            parsed_tournament.title, parsed_tournament.date, to
+        )));
+    }
+
+    let announcement = webhook::Announcement::new(&parsed_tournament);
+    let mut entry = AuditEntry::new(&to_config, "promote", to_db_name);
+    entry.source = Some(format!("{}:{}", from, tournament_id));
+    let summary = audit::record_insert(
+        &to_config,
+        entry,
+        db::insert_parsed_tournament(
+            to_conn,
+            parsed_tournament,
+            &to_config.scoring,
+            &to_config.stat_points,
+        ),
+    )?;
+    webhook::announce(&to_config, &announcement, summary.tournament_id);
+    cmd_step(
+        format!(
+            "Inserted Tournament Into '{}' [id {}]",
+            to, summary.tournament_id
+        )
+        .green(),
+        6,
+        PROMOTE_STEPS,
+    );
+
+    output::line(
+        format!(
+            "\n[Finished Promoting Tournament {} To {}]",
+            tournament_id, to
+        )
+        .green()
+        .bold(),
+    );
+
+    Ok(json!({ "from": from, "to": to, "source_tournament_id": tournament_id, "rows": summary }))
+}
+
+pub fn backup(archive_file: &str, config: &Config) -> Result<Value, CliError> {
+    cmd_step(
+        "Connecting To MySql Database...".bright_black().italic(),
+        1,
+        BACKUP_STEPS,
+    );
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+    cmd_step(
+        format!("Established MySql Database Connection [{}]", db_name).green(),
+        2,
+        BACKUP_STEPS,
+    );
+
+    let archive = backup::dump(db_conn)?;
+    cmd_step(
+        format!("Read {}", archive.counts()).green(),
+        3,
+        BACKUP_STEPS,
+    );
+
+    backup::write_archive(archive_file, &archive)?;
+    cmd_step(
+        format!("Wrote Archive To {}", archive_file).green(),
+        4,
+        BACKUP_STEPS,
+    );
+
+    output::line("\n[Finished Backup]".green().bold());
+
+    Ok(json!({ "file": archive_file, "rows": archive.counts() }))
+}
+
+pub fn restore(archive_file: &str, config: &Config) -> Result<Value, CliError> {
+    let archive = backup::read_archive(archive_file)?;
+    cmd_step(
+        format!("Read Archive: {}", archive.counts()).green(),
+        1,
+        RESTORE_STEPS,
+    );
+
+    cmd_step(
+        "Connecting To MySql Database...".bright_black().italic(),
+        2,
+        RESTORE_STEPS,
+    );
+    let (db_conn, db_name) =
+        &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+    cmd_step(
+        format!("Established MySql Database Connection [{}]", db_name).green(),
+        3,
+        RESTORE_STEPS,
+    );
+
+    let mut entry = AuditEntry::new(config, "restore", db_name).source_file(archive_file);
+    if let Err(error) = backup::restore(db_conn, &archive) {
+        audit::record(config, &entry.failed(&error));
+        return Err(error);
+    }
+    cmd_step("Restored Archive Into Database".green(), 4, RESTORE_STEPS);
+
+    let counts = archive.counts();
+    entry.rows = AuditRows {
+        tournaments: Some(counts.tournaments),
+        matches: Some(counts.matches),
+        match_teams: Some(counts.match_teams),
+        match_users: Some(counts.match_users),
+        match_user_stats: Some(counts.match_user_stats),
+        users: Some(counts.users),
+        new_users: Some(counts.users),
+        renamed_users: None,
+        user_aliases: Some(counts.user_aliases),
+        next_tournaments: Some(counts.next_tournaments),
+        seasons: Some(counts.seasons),
+        tournament_users: Some(counts.tournament_users),
+        tournament_results: Some(counts.tournament_results),
+    };
+    audit::record(config, &entry);
+
+    output::line("\n[Finished Restore]".green().bold());
+
+    Ok(json!({ "file": archive_file, "rows": counts }))
+}
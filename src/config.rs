@@ -0,0 +1,268 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use dotenvy::{dotenv, from_filename};
+use serde::Deserialize;
+
+use crate::audit;
+use crate::db::RetryPolicy;
+use crate::error::CliError;
+use crate::rounds::{Formats, RoundFormat};
+
+const CONFIG_DIR_NAME: &str = "pbt-cli";
+const CONFIG_FILE_NAME: &str = "config.toml";
+const EXE_CONFIG_FILE_NAME: &str = "pbt-cli.toml";
+const AUDIT_LOG_FILE_NAME: &str = "audit.log";
+
+#[derive(Debug, Deserialize, Default)]
+pub struct ConfigFile {
+    pub default_profile: Option<String>,
+    /// Name written to the audit log, defaults to the OS user
+    pub operator: Option<String>,
+    pub audit_log: Option<PathBuf>,
+    #[serde(default)]
+    pub profiles: HashMap<String, Profile>,
+    /// Round structures by name, `default` is built in
+    #[serde(default)]
+    pub formats: HashMap<String, RoundFormat>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct Profile {
+    pub database_url: Option<String>,
+    pub connect_retries: Option<u32>,
+    pub connect_backoff_ms: Option<u64>,
+    #[serde(default)]
+    pub scoring: ScoringSystem,
+    #[serde(default)]
+    pub defaults: PromptDefaults,
+    /// Name of the round format used by this profile
+    pub format: Option<String>,
+    /// Extra ranking points per unit of a player stat, e.g. `kills = 2`
+    #[serde(default)]
+    pub stat_points: HashMap<String, i32>,
+    /// Bearer token `serve` requires on every request
+    pub api_token: Option<String>,
+    /// Discord webhook the results of every upload are posted to
+    pub webhook_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct PromptDefaults {
+    pub title: Option<String>,
+    pub link: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(tag = "system", rename_all = "lowercase")]
+pub enum ScoringSystem {
+    /// Last place gets 1 point, every placement above gets 1 more
+    #[default]
+    Linear,
+    /// Fixed points per placement, everyone past the end of the table gets 0
+    Table { points: Vec<i32> },
+}
+
+impl ScoringSystem {
+    /// `placement` is zero-based, `total` is the amount of placed players
+    pub fn points(&self, placement: usize, total: usize) -> i32 {
+        match self {
+            ScoringSystem::Linear => (total - placement) as i32,
+            ScoringSystem::Table { points } => points.get(placement).copied().unwrap_or(0),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Config {
+    pub path: Option<PathBuf>,
+    pub profile_name: Option<String>,
+    pub database_url: String,
+    pub retry_policy: RetryPolicy,
+    pub scoring: ScoringSystem,
+    pub stat_points: HashMap<String, i32>,
+    pub defaults: PromptDefaults,
+    pub operator: String,
+    pub audit_log: PathBuf,
+    pub formats: Formats,
+    pub api_token: Option<String>,
+    pub webhook_url: Option<String>,
+}
+
+/// Loads the config file and selects a profile.
+///
+/// The file is taken from `config_path` if given, otherwise the first existing of
+/// `<config dir>/pbt-cli/config.toml` and `pbt-cli.toml` next to the binary.
+/// Without a config file (or a profile without a database url) it falls back to the old
+/// `.env` / `.release.env` files.
+pub fn load(config_path: Option<&str>, profile_name: Option<&str>) -> Result<Config, CliError> {
+    let (path, file) = locate(config_path)?;
+    let (profile_name, profile) = select_profile(&path, &file, profile_name)?;
+
+    let database_url = match &profile.database_url {
+        Some(url) => url.to_owned(),
+        None => {
+            match cfg!(debug_assertions) {
+                true => dotenv().ok(),
+                false => from_filename(".release.env").ok(),
+            };
+            env::var("DATABASE_URL").map_err(|_| {
+                CliError::Config(String::from("No DATABASE_URL In Config Profile Or .env"))
+            })?
+        }
+    };
+
+    let mut retry_policy = RetryPolicy::from_env();
+    if let Some(retries) = profile.connect_retries {
+        retry_policy.retries = retries;
+    }
+    if let Some(backoff_ms) = profile.connect_backoff_ms {
+        retry_policy.backoff = std::time::Duration::from_millis(backoff_ms);
+    }
+
+    let audit_log = file.audit_log.unwrap_or_else(|| {
+        user_config_dir()
+            .map(|dir| dir.join(CONFIG_DIR_NAME).join(AUDIT_LOG_FILE_NAME))
+            .unwrap_or(PathBuf::from(AUDIT_LOG_FILE_NAME))
+    });
+
+    Ok(Config {
+        path,
+        profile_name,
+        database_url,
+        retry_policy,
+        scoring: profile.scoring,
+        stat_points: profile.stat_points,
+        defaults: profile.defaults,
+        operator: file.operator.unwrap_or_else(audit::default_operator),
+        audit_log,
+        formats: Formats {
+            profile_format: profile.format,
+            formats: file.formats,
+        },
+        api_token: profile.api_token,
+        webhook_url: profile.webhook_url,
+    })
+}
+
+/// Only the round formats, for commands that don't touch the database
+pub fn load_formats(
+    config_path: Option<&str>,
+    profile_name: Option<&str>,
+) -> Result<Formats, CliError> {
+    load_profile(config_path, profile_name).map(|(_, formats)| formats)
+}
+
+/// The selected profile as written in the config, with the round formats.
+/// Unlike `load` this works without a database url
+pub fn load_profile(
+    config_path: Option<&str>,
+    profile_name: Option<&str>,
+) -> Result<(Profile, Formats), CliError> {
+    let (path, file) = locate(config_path)?;
+    let (_, profile) = select_profile(&path, &file, profile_name)?;
+
+    let formats = Formats {
+        profile_format: profile.format.to_owned(),
+        formats: file.formats,
+    };
+
+    Ok((profile, formats))
+}
+
+fn locate(config_path: Option<&str>) -> Result<(Option<PathBuf>, ConfigFile), CliError> {
+    let path = match config_path {
+        Some(path) => {
+            let path = PathBuf::from(path);
+            if !path.is_file() {
+                return Err(CliError::Config(format!(
+                    "Config file not found: {}",
+                    path.display()
+                )));
+            }
+            Some(path)
+        }
+        None => find_config_file(),
+    };
+
+    let file = match &path {
+        Some(path) => read_config_file(path)?,
+        None => ConfigFile::default(),
+    };
+
+    Ok((path, file))
+}
+
+fn select_profile(
+    path: &Option<PathBuf>,
+    file: &ConfigFile,
+    profile_name: Option<&str>,
+) -> Result<(Option<String>, Profile), CliError> {
+    let profile_name = profile_name
+        .map(|p| p.to_owned())
+        .or(file.default_profile.clone());
+
+    let profile = match &profile_name {
+        Some(name) => file.profiles.get(name).cloned().ok_or_else(|| {
+            CliError::Config(format!(
+                "No profile named '{}' in {}",
+                name,
+                path.as_ref()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or(String::from("config (no config file found)"))
+            ))
+        })?,
+        None => Profile::default(),
+    };
+
+    Ok((profile_name, profile))
+}
+
+fn read_config_file(path: &Path) -> Result<ConfigFile, CliError> {
+    let content = fs::read_to_string(path).map_err(|error| {
+        CliError::Config(format!(
+            "Failed to read config {}, {}",
+            path.display(),
+            error
+        ))
+    })?;
+
+    toml::from_str(&content).map_err(|error| {
+        CliError::Config(format!(
+            "Failed to parse config {}, {}",
+            path.display(),
+            error
+        ))
+    })
+}
+
+fn find_config_file() -> Option<PathBuf> {
+    let mut candidates: Vec<PathBuf> = vec![];
+
+    if let Some(config_dir) = user_config_dir() {
+        candidates.push(config_dir.join(CONFIG_DIR_NAME).join(CONFIG_FILE_NAME));
+    }
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|p| p.to_path_buf()))
+    {
+        candidates.push(exe_dir.join(EXE_CONFIG_FILE_NAME));
+    }
+
+    candidates.into_iter().find(|path| path.is_file())
+}
+
+// $XDG_CONFIG_HOME, falling back to ~/.config, or %APPDATA% on windows
+fn user_config_dir() -> Option<PathBuf> {
+    if cfg!(windows) {
+        return env::var_os("APPDATA").map(PathBuf::from);
+    }
+
+    env::var_os("XDG_CONFIG_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+}
@@ -1,61 +1,375 @@
-use diesel::connection::SimpleConnection;
 use diesel::mysql::MysqlConnection;
 use diesel::prelude::*;
 
-use dotenvy::{dotenv, from_filename};
-use std::{env, vec};
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use std::{collections::HashMap, env, thread, time::Duration, vec};
 use url::Url;
 use uuid::Uuid;
 
-use crate::models::{self, NewMatch, NewMatchUser, NewTournament, NewUser, Tournament};
-use crate::tournament_parser::{self, MatchUser, ParsedTournament};
+use crate::config::ScoringSystem;
+use crate::error::{CliError, DbContext};
+use crate::models::{
+    self, NewMatch, NewMatchUser, NewMatchUserStat, NewSeason, NewTournament, NewTournamentResult,
+    NewUser, NewUserAlias, Season, Tournament, User, UserAlias,
+};
+use crate::output;
+use crate::rounds::{Formats, DEFAULT_FORMAT};
+use crate::tournament_parser::{
+    self, ImmuneStatus, LifeStatus, ParsedTournament, TournamentHeader,
+};
 
-pub fn establish_connection() -> (MysqlConnection, String) {
-    match cfg!(debug_assertions) {
-        true => dotenv().ok(),
-        false => from_filename(".release.env").ok(),
-    };
+const DEFAULT_CONNECT_RETRIES: u32 = 3;
+const DEFAULT_CONNECT_BACKOFF_MS: u64 = 500;
+const MAX_CONNECT_BACKOFF_MS: u64 = 8000;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionErrorKind {
+    Dns,
+    Unreachable,
+    Auth,
+    Tls,
+    UnknownDatabase,
+    Unknown,
+}
+
+impl ConnectionErrorKind {
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            ConnectionErrorKind::Dns => "DNS",
+            ConnectionErrorKind::Unreachable => "Unreachable",
+            ConnectionErrorKind::Auth => "Auth",
+            ConnectionErrorKind::Tls => "TLS",
+            ConnectionErrorKind::UnknownDatabase => "Unknown Database",
+            ConnectionErrorKind::Unknown => "Unknown",
+        }
+    }
+
+    pub fn hint(&self) -> &'static str {
+        match self {
+            ConnectionErrorKind::Dns => "Check the host name in the database url",
+            ConnectionErrorKind::Unreachable => {
+                "Check that the server is running and the port is reachable"
+            }
+            ConnectionErrorKind::Auth => "Check the user name and password in the database url",
+            ConnectionErrorKind::Tls => "Check the server's SSL/TLS settings and certificates",
+            ConnectionErrorKind::UnknownDatabase => "Check the database name in the database url",
+            ConnectionErrorKind::Unknown => "See the error above for details",
+        }
+    }
+
+    // Retrying won't fix wrong credentials, a missing database or a broken TLS setup
+    fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ConnectionErrorKind::Dns
+                | ConnectionErrorKind::Unreachable
+                | ConnectionErrorKind::Unknown
+        )
+    }
+
+    fn classify(message: &str) -> ConnectionErrorKind {
+        let message = message.to_lowercase();
+
+        if message.contains("unknown mysql server host")
+            || message.contains("name or service not known")
+            || message.contains("no such host")
+        {
+            ConnectionErrorKind::Dns
+        } else if message.contains("access denied") {
+            ConnectionErrorKind::Auth
+        } else if message.contains("ssl") || message.contains("tls") {
+            ConnectionErrorKind::Tls
+        } else if message.contains("unknown database") {
+            ConnectionErrorKind::UnknownDatabase
+        } else if message.contains("can't connect to mysql server")
+            || message.contains("lost connection")
+            || message.contains("connection refused")
+            || message.contains("timed out")
+        {
+            ConnectionErrorKind::Unreachable
+        } else {
+            ConnectionErrorKind::Unknown
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub retries: u32,
+    pub backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Reads `DATABASE_CONNECT_RETRIES` & `DATABASE_CONNECT_BACKOFF_MS`, falling back to the defaults
+    pub fn from_env() -> RetryPolicy {
+        let retries = env::var("DATABASE_CONNECT_RETRIES")
+            .ok()
+            .and_then(|v| v.parse::<u32>().ok())
+            .unwrap_or(DEFAULT_CONNECT_RETRIES);
+        let backoff_ms = env::var("DATABASE_CONNECT_BACKOFF_MS")
+            .ok()
+            .and_then(|v| v.parse::<u64>().ok())
+            .unwrap_or(DEFAULT_CONNECT_BACKOFF_MS);
+
+        RetryPolicy {
+            retries,
+            backoff: Duration::from_millis(backoff_ms),
+        }
+    }
+
+    fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u64.saturating_pow(attempt);
+        let delay_ms = (self.backoff.as_millis() as u64).saturating_mul(factor);
+
+        Duration::from_millis(delay_ms.min(MAX_CONNECT_BACKOFF_MS))
+    }
+}
+
+/// Replaces the password in a database url with `***` so it can be printed
+pub fn redact_url(database_url: &str) -> String {
+    match Url::parse(database_url) {
+        Ok(mut url) => {
+            if url.password().is_some() {
+                let _ = url.set_password(Some("***"));
+            }
+            url.to_string()
+        }
+        Err(_) => String::from("<invalid database url>"),
+    }
+}
+
+fn redact_message(message: &str, database_url: &str) -> String {
+    let mut redacted = message.replace(database_url, &redact_url(database_url));
+
+    if let Some(password) = Url::parse(database_url)
+        .ok()
+        .and_then(|url| url.password().map(|p| p.to_owned()))
+        .filter(|p| !p.is_empty())
+    {
+        redacted = redacted.replace(&password, "***");
+    }
+
+    redacted
+}
 
-    let database_url = env::var("DATABASE_URL").expect("No DATABASE_URL In .env");
-    let database_name = Url::parse(&database_url)
-        .expect("Failed to parse database url")
+pub fn establish_connection(
+    database_url: &str,
+    policy: &RetryPolicy,
+) -> Result<(MysqlConnection, String), CliError> {
+    let database_name = Url::parse(database_url)
+        .map_err(|error| CliError::Config(format!("Failed to parse database url, {}", error)))?
         .path()
         .to_owned();
 
-    let conn = MysqlConnection::establish(&database_url).unwrap_or_else(|error| {
-        panic!(
-            "Error Connecting To {}, {}",
-            database_url,
-            error.to_string()
-        )
-    });
+    let mut attempt: u32 = 0;
+
+    loop {
+        let error = match MysqlConnection::establish(database_url) {
+            Ok(conn) => return Ok((conn, database_name)),
+            Err(error) => error,
+        };
+
+        let message = redact_message(&error.to_string(), database_url);
+        let kind = ConnectionErrorKind::classify(&message);
+
+        if !kind.is_retryable() || attempt >= policy.retries {
+            return Err(CliError::Connection(format!(
+                "Error Connecting To {} [{}]\n◆ {}\n◆ {}",
+                redact_url(database_url),
+                kind.to_str(),
+                message,
+                kind.hint()
+            )));
+        }
 
-    return (conn, database_name);
+        let delay = policy.delay(attempt);
+        attempt += 1;
+        output::warning(
+            &format!(
+                "Connection attempt {}/{} failed [{}]: ",
+                attempt,
+                policy.retries + 1,
+                kind.to_str()
+            ),
+            &format!("{}, retrying in {}s", message, delay.as_secs_f32()),
+        );
+        thread::sleep(delay);
+    }
 }
 
-pub fn insert_parsed_tournament(conn: &mut MysqlConnection, parsed_tournament: ParsedTournament) {
-    let db_tournament = create_tournament(
-        conn,
-        NewTournament {
-            version: parsed_tournament.version,
-            date: parsed_tournament.date,
-            title: parsed_tournament.title,
-            link: parsed_tournament.link,
-        },
-    );
+/// Row counts written by `insert_parsed_tournament`
+#[derive(Serialize, Debug, Clone)]
+pub struct InsertSummary {
+    pub tournament_id: i32,
+    pub matches: usize,
+    pub match_teams: usize,
+    pub match_users: usize,
+    pub match_user_stats: usize,
+    /// Users created or updated
+    pub users: usize,
+    pub new_users: usize,
+    pub renamed_users: usize,
+    pub tournament_users: usize,
+    pub tournament_results: usize,
+}
+
+/// Writes a finished tournament with its results, all or nothing so the stored results
+/// always match the users' wins & rankings
+pub fn insert_parsed_tournament(
+    conn: &mut MysqlConnection,
+    parsed_tournament: ParsedTournament,
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Result<InsertSummary, CliError> {
+    conn.transaction::<_, CliError, _>(|conn| {
+        let season_id = tournament_season_id(conn, &parsed_tournament)?;
+        let db_tournament = create_tournament(conn, new_tournament(&parsed_tournament, season_id))?;
+
+        let mut summary = create_tournament_matches(conn, db_tournament.id, &parsed_tournament)?;
+        (summary.tournament_users, summary.tournament_results) = apply_tournament_results(
+            conn,
+            db_tournament.id,
+            &parsed_tournament,
+            scoring,
+            stat_points,
+        )?;
+
+        Ok(summary)
+    })
+}
+
+/// Creates the tournament row of a live tournament, its matches are written with
+/// `replace_live_matches` every time the file changes
+pub fn create_live_tournament(
+    conn: &mut MysqlConnection,
+    parsed_tournament: &ParsedTournament,
+) -> Result<i32, CliError> {
+    let season_id = tournament_season_id(conn, parsed_tournament)?;
+
+    Ok(create_tournament(conn, new_tournament(parsed_tournament, season_id))?.id)
+}
+
+/// Swaps the matches of a live tournament for the current state of its file,
+/// user wins & rankings are left alone until `finish_live_tournament`
+pub fn replace_live_matches(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+    parsed_tournament: &ParsedTournament,
+) -> Result<InsertSummary, CliError> {
+    conn.transaction::<_, CliError, _>(|conn| {
+        delete_tournament_matches(conn, tournament_id)?;
+        create_tournament_matches(conn, tournament_id, parsed_tournament)
+    })
+}
+
+/// Writes the completed matches of a live tournament and applies the wins, rankings &
+/// tournament links, all in one transaction
+pub fn finish_live_tournament(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+    parsed_tournament: &ParsedTournament,
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Result<InsertSummary, CliError> {
+    use crate::schema::Tournament;
+
+    conn.transaction::<_, CliError, _>(|conn| {
+        ensure_not_finished(conn, tournament_id)?;
+        // The date may have changed since the tournament was created
+        let season_id = tournament_season_id(conn, parsed_tournament)?;
+        diesel::update(Tournament::table.find(tournament_id))
+            .set((
+                Tournament::title.eq(&parsed_tournament.title),
+                Tournament::date.eq(parsed_tournament.date),
+                Tournament::link.eq(&parsed_tournament.link),
+                Tournament::seasonId.eq(season_id),
+                Tournament::format.eq(&parsed_tournament.format.name),
+            ))
+            .execute(conn)
+            .db_context("Failed to update tournament")?;
+        delete_tournament_matches(conn, tournament_id)?;
+
+        let mut summary = create_tournament_matches(conn, tournament_id, parsed_tournament)?;
+        (summary.tournament_users, summary.tournament_results) =
+            apply_tournament_results(conn, tournament_id, parsed_tournament, scoring, stat_points)?;
+
+        Ok(summary)
+    })
+}
+
+fn new_tournament(parsed_tournament: &ParsedTournament, season_id: Option<i32>) -> NewTournament {
+    NewTournament {
+        version: parsed_tournament.version,
+        date: parsed_tournament.date,
+        title: parsed_tournament.title.to_owned(),
+        link: parsed_tournament.link.to_owned(),
+        seasonId: season_id,
+        format: Some(parsed_tournament.format.name.to_owned()),
+    }
+}
+
+/// The season named by the tournament, or else the one its date falls in
+fn tournament_season_id(
+    conn: &mut MysqlConnection,
+    parsed_tournament: &ParsedTournament,
+) -> Result<Option<i32>, CliError> {
+    use crate::schema::Season;
+
+    match &parsed_tournament.season {
+        Some(name) => get_season(conn, name)?
+            .map(|season| Some(season.id))
+            .ok_or_else(|| {
+                CliError::Usage(format!(
+                    "No season named '{}', create it with `pbt-cli season add`",
+                    name
+                ))
+            }),
+        None => Season::table
+            .filter(Season::startsAt.le(parsed_tournament.date))
+            .filter(Season::endsAt.ge(parsed_tournament.date))
+            .select(Season::id)
+            .first(conn)
+            .optional()
+            .db_context("Failed to look up season"),
+    }
+}
 
-    let mut matches: Vec<(NewMatch, Vec<NewMatchUser>)> = vec![];
-    let mut users: Vec<NewUser> = vec![];
+/// Creates the matches, teams, match users & stats of a tournament and its missing users
+fn create_tournament_matches(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+    parsed_tournament: &ParsedTournament,
+) -> Result<InsertSummary, CliError> {
+    let mut matches: Vec<MatchRows> = vec![];
 
     for m in parsed_tournament.matches.clone() {
         let new_m = NewMatch {
             id: Uuid::new_v4().to_string(),
-            matchType: m.match_type.to_str().to_owned(),
+            matchType: m.match_type.key.to_owned(),
             matchIndex: m.match_index,
-            tournamentId: Some(db_tournament.id),
+            tournamentId: Some(tournament_id),
+            map: m.info.map.to_owned(),
+            mode: m.info.mode.to_owned(),
+            startedAt: m.info.started_at,
+            durationSecs: m.info.duration_secs,
+            notes: m.info.notes.to_owned(),
         };
 
+        let match_teams: Vec<models::MatchTeam> = m
+            .teams
+            .iter()
+            .map(|team| models::MatchTeam {
+                id: Uuid::new_v4().to_string(),
+                name: team.name.to_owned(),
+                rank: team.rank,
+                lifeStatus: team.life_status.to_str().to_owned(),
+                matchId: new_m.id.clone(),
+            })
+            .collect();
+
         let mut match_users: Vec<NewMatchUser> = vec![];
+        let mut match_user_stats: Vec<NewMatchUserStat> = vec![];
         for user in m.players {
             let new_match_user = NewMatchUser {
                 id: Uuid::new_v4().to_string(),
@@ -65,52 +379,904 @@ pub fn insert_parsed_tournament(conn: &mut MysqlConnection, parsed_tournament: P
                 lifeStatus: user.life_status.to_str().to_owned(),
                 immuneStatus: user.immune_status.to_str().to_owned(),
                 matchId: new_m.id.clone(),
+                teamId: match_teams
+                    .iter()
+                    .find(|team| Some(&team.name) == user.team.as_ref())
+                    .map(|team| team.id.to_owned()),
             };
+            match_user_stats.extend(user.stats.iter().map(|(name, value)| NewMatchUserStat {
+                matchUserId: new_match_user.id.to_owned(),
+                name: name.to_owned(),
+                value: *value,
+            }));
             match_users.push(new_match_user);
+        }
+
+        matches.push(MatchRows {
+            new_match: new_m,
+            teams: match_teams,
+            users: match_users,
+            stats: match_user_stats,
+        });
+    }
 
-            fn user_exists(new_id: &String, users: &Vec<NewUser>) -> bool {
-                for user in users {
-                    if &user.userId == new_id {
-                        return true;
-                    }
-                }
+    let users: Vec<NewUser> = tournament_parser::get_unique_players(&parsed_tournament.matches)
+        .into_iter()
+        .map(|user| NewUser {
+            userId: user.user_id,
+            username: user.username,
+            wins: 0,
+            ranking: 0,
+        })
+        .collect();
 
-                return false;
-            }
+    let mut summary = InsertSummary {
+        tournament_id,
+        matches: matches.len(),
+        match_teams: matches.iter().map(|m| m.teams.len()).sum(),
+        match_users: matches.iter().map(|m| m.users.len()).sum(),
+        match_user_stats: matches.iter().map(|m| m.stats.len()).sum(),
+        users: users.len(),
+        new_users: 0,
+        renamed_users: 0,
+        tournament_users: 0,
+        tournament_results: 0,
+    };
 
-            if !user_exists(&user.user_id, &users) {
-                let new_user = NewUser {
-                    userId: user.user_id.to_owned(),
-                    username: user.username.to_owned(),
-                    wins: 0,
-                    ranking: 0,
-                };
-                users.push(new_user);
-            }
+    (summary.new_users, summary.renamed_users) =
+        create_users(conn, users, tournament_id, parsed_tournament.date)?;
+    create_matches(conn, matches)?;
+
+    Ok(summary)
+}
+
+/// Stores the results of a finished tournament, adds them to the users' wins & rankings
+/// and links its players to it. Returns the amount of tournament links & results
+fn apply_tournament_results(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+    parsed_tournament: &ParsedTournament,
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Result<(usize, usize), CliError> {
+    use crate::schema::TournamentResult;
+
+    let results = tournament_results(tournament_id, parsed_tournament, scoring, stat_points)?;
+    diesel::insert_into(TournamentResult::table)
+        .values(&results)
+        .execute(conn)
+        .db_context("Failed to insert tournament results")?;
+    for result in &results {
+        let wins = i32::from(result.placement == Some(1));
+        update_user_counters(
+            conn,
+            &result.userId,
+            wins,
+            result.points + result.statPoints,
+        )?;
+    }
+
+    let placements = tournament_parser::get_placements(parsed_tournament);
+    let mut linked_tournament_users: Vec<(i32, String)> = vec![];
+    // Might as well utilize the placements for this
+    for user in placements.into_iter().flatten() {
+        linked_tournament_users.push((tournament_id, user.user_id));
+    }
+    let tournament_users = linked_tournament_users.len();
+    create_tournament_user_link(conn, linked_tournament_users)
+        .db_context("Failed to batch insert tournament to user fields")?;
+
+    Ok((tournament_users, results.len()))
+}
+
+/// Placement points & stat points per player of a finished tournament.
+/// A winning team's members all get the win, and every member gets the team's points
+fn tournament_results(
+    tournament_id: i32,
+    parsed_tournament: &ParsedTournament,
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Result<Vec<NewTournamentResult>, CliError> {
+    let placements = tournament_parser::get_placements(parsed_tournament);
+    if placements.is_empty() {
+        return Err(CliError::Parse(String::from("No first user in overall")));
+    }
+
+    let mut results: Vec<NewTournamentResult> = vec![];
+    for (i, users) in placements.iter().enumerate() {
+        let points = scoring.points(i, placements.len());
+        for user in users {
+            results.push(NewTournamentResult {
+                tournamentId: tournament_id,
+                userId: user.user_id.to_owned(),
+                placement: Some(i as i32 + 1),
+                points,
+                statPoints: 0,
+            });
+        }
+    }
+
+    for (user, points) in parsed_tournament.stat_points(stat_points) {
+        match results.iter_mut().find(|r| r.userId == user.user_id) {
+            Some(result) => result.statPoints += points,
+            None => results.push(NewTournamentResult {
+                tournamentId: tournament_id,
+                userId: user.user_id.to_owned(),
+                placement: None,
+                points: 0,
+                statPoints: points,
+            }),
+        }
+    }
+
+    Ok(results)
+}
+
+/// Reads a tournament back into the same shape the parser produces, with the format it was
+/// uploaded with unless `format` names another. Tournaments from before formats were stored
+/// used the built in rounds
+pub fn get_parsed_tournament(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+    formats: &Formats,
+    format: Option<&str>,
+) -> Result<Option<ParsedTournament>, CliError> {
+    use crate::schema::MatchTeam;
+    use crate::schema::MatchUser;
+    use crate::schema::MatchUserStat;
+    use crate::schema::Match_;
+    use crate::schema::Tournament;
+
+    let tournament = match Tournament::table
+        .find(tournament_id)
+        .select(models::Tournament::as_select())
+        .first(conn)
+        .optional()
+        .db_context("Failed to get tournament")?
+    {
+        Some(tournament) => tournament,
+        None => return Ok(None),
+    };
+    let format = formats.get(Some(
+        format
+            .or(tournament.format.as_deref())
+            .unwrap_or(DEFAULT_FORMAT),
+    ))?;
+
+    let db_matches = Match_::table
+        .filter(Match_::tournamentId.eq(tournament.id))
+        .order(Match_::matchIndex.asc())
+        .select(models::Match::as_select())
+        .load(conn)
+        .db_context("Failed to get tournament matches")?;
+
+    let mut matches: Vec<tournament_parser::Match> = vec![];
+    for db_match in db_matches {
+        let match_users = MatchUser::table
+            .filter(MatchUser::matchId.eq(&db_match.id))
+            .order(MatchUser::rank.asc())
+            .select(models::MatchUser::as_select())
+            .load(conn)
+            .db_context("Failed to get match users")?;
+        let match_teams = MatchTeam::table
+            .filter(MatchTeam::matchId.eq(&db_match.id))
+            .order(MatchTeam::rank.asc())
+            .select(models::MatchTeam::as_select())
+            .load(conn)
+            .db_context("Failed to get match teams")?;
+        let match_user_ids: Vec<&String> = match_users.iter().map(|user| &user.id).collect();
+        let match_user_stats = MatchUserStat::table
+            .filter(MatchUserStat::matchUserId.eq_any(match_user_ids))
+            .select(models::MatchUserStat::as_select())
+            .load(conn)
+            .db_context("Failed to get match user stats")?;
+
+        let mut players: Vec<tournament_parser::MatchUser> = vec![];
+        for user in match_users {
+            players.push(tournament_parser::MatchUser {
+                rank: user.rank,
+                life_status: LifeStatus::from_key(&user.lifeStatus).ok_or_else(|| {
+                    CliError::Database(format!("Unknown life status: {}", user.lifeStatus))
+                })?,
+                immune_status: ImmuneStatus::from_key(&user.immuneStatus).ok_or_else(|| {
+                    CliError::Database(format!("Unknown immune status: {}", user.immuneStatus))
+                })?,
+                username: user.username,
+                user_id: user.userId,
+                team: match_teams
+                    .iter()
+                    .find(|team| Some(&team.id) == user.teamId.as_ref())
+                    .map(|team| team.name.to_owned()),
+                stats: match_user_stats
+                    .iter()
+                    .filter(|stat| stat.matchUserId == user.id)
+                    .map(|stat| (stat.name.to_owned(), stat.value))
+                    .collect(),
+                invalid_id: None,
+            });
         }
 
-        matches.push((new_m, match_users));
+        let mut teams: Vec<tournament_parser::MatchTeam> = vec![];
+        for team in match_teams {
+            teams.push(tournament_parser::MatchTeam {
+                rank: team.rank,
+                life_status: LifeStatus::from_key(&team.lifeStatus).ok_or_else(|| {
+                    CliError::Database(format!("Unknown life status: {}", team.lifeStatus))
+                })?,
+                name: team.name,
+            });
+        }
+
+        matches.push(tournament_parser::Match {
+            match_type: format.by_key(&db_match.matchType).cloned().ok_or_else(|| {
+                CliError::Database(format!(
+                    "Unknown match type '{}', it's not a round of the format: {}",
+                    db_match.matchType,
+                    format.names()
+                ))
+            })?,
+            players,
+            teams,
+            info: tournament_parser::MatchInfo {
+                map: db_match.map,
+                mode: db_match.mode,
+                started_at: db_match.startedAt,
+                duration_secs: db_match.durationSecs,
+                notes: db_match.notes,
+            },
+            match_index: db_match.matchIndex,
+        });
     }
 
-    create_users(conn, users);
-    create_matches(conn, matches);
+    Ok(Some(ParsedTournament {
+        version: tournament.version,
+        format,
+        matches,
+        date: tournament.date,
+        title: tournament.title,
+        link: tournament.link,
+        // Season names differ between databases, `promote` files it by date instead
+        season: None,
+        header: TournamentHeader::default(),
+    }))
+}
+
+pub fn get_tournament(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<Option<Tournament>, CliError> {
+    use crate::schema::Tournament;
+
+    Tournament::table
+        .find(tournament_id)
+        .select(models::Tournament::as_select())
+        .first(conn)
+        .optional()
+        .db_context("Failed to get tournament")
+}
+
+pub fn tournament_exists(
+    conn: &mut MysqlConnection,
+    title: &str,
+    date: NaiveDateTime,
+) -> Result<bool, CliError> {
+    use crate::schema::Tournament;
+
+    let count: i64 = Tournament::table
+        .filter(Tournament::title.eq(title))
+        .filter(Tournament::date.eq(date))
+        .count()
+        .get_result(conn)
+        .db_context("Failed to look up tournament")?;
+
+    Ok(count > 0)
+}
+
+/// The existing users out of `user_ids`
+pub fn get_users(conn: &mut MysqlConnection, user_ids: &[String]) -> Result<Vec<User>, CliError> {
+    use crate::schema::User;
 
-    let overall = tournament_parser::get_overall_player_list(parsed_tournament.matches.clone());
-    update_user_win(
-        conn,
-        overall.first().expect("No first user in overall").clone(),
+    User::table
+        .filter(User::userId.eq_any(user_ids))
+        .select(models::User::as_select())
+        .load(conn)
+        .db_context("Failed to get users")
+}
+
+/// Users whose current or any previous username contains `name`, with their previous names
+pub fn find_users(
+    conn: &mut MysqlConnection,
+    name: &str,
+) -> Result<Vec<(User, Vec<UserAlias>)>, CliError> {
+    use crate::schema::User;
+    use crate::schema::UserAlias;
+
+    let pattern = format!("%{}%", name.replace('%', "\\%").replace('_', "\\_"));
+    let alias_user_ids: Vec<String> = UserAlias::table
+        .filter(UserAlias::username.like(&pattern))
+        .select(UserAlias::userId)
+        .distinct()
+        .load(conn)
+        .db_context("Failed to search user aliases")?;
+
+    let users = User::table
+        .filter(
+            User::username
+                .like(&pattern)
+                .or(User::userId.eq_any(&alias_user_ids))
+                .or(User::userId.eq(name)),
+        )
+        .order(User::username.asc())
+        .select(models::User::as_select())
+        .load(conn)
+        .db_context("Failed to search users")?;
+
+    let mut found: Vec<(models::User, Vec<models::UserAlias>)> = vec![];
+    for user in users {
+        let aliases = UserAlias::table
+            .filter(UserAlias::userId.eq(&user.userId))
+            .order(UserAlias::changedAt.desc())
+            .select(models::UserAlias::as_select())
+            .load(conn)
+            .db_context("Failed to get user aliases")?;
+        found.push((user, aliases));
+    }
+
+    Ok(found)
+}
+
+/// Every user id with its current & previous usernames
+pub fn get_all_usernames(conn: &mut MysqlConnection) -> Result<Vec<(String, String)>, CliError> {
+    use crate::schema::User;
+    use crate::schema::UserAlias;
+
+    let mut usernames: Vec<(String, String)> = User::table
+        .select((User::userId, User::username))
+        .load(conn)
+        .db_context("Failed to get usernames")?;
+    usernames.extend(
+        UserAlias::table
+            .select((UserAlias::userId, UserAlias::username))
+            .load::<(String, String)>(conn)
+            .db_context("Failed to get user aliases")?,
     );
-    update_user_ranks(conn, overall.clone());
 
-    let mut linked_tournament_users: Vec<(i32, String)> = vec![];
-    // Might as well utilize the overall vec for this
-    for user in overall {
-        linked_tournament_users.push((db_tournament.id, user.user_id));
+    Ok(usernames)
+}
+
+/// Rows changed by `merge_users`
+#[derive(Serialize, Debug)]
+pub struct MergeSummary {
+    pub match_users: usize,
+    pub tournament_users: usize,
+    pub tournament_results: usize,
+    pub user_aliases: usize,
+    pub wins: i32,
+    pub ranking: i32,
+}
+
+/// Moves everything of `drop_id` over to `keep_id` & deletes the dropped user, all or nothing
+pub fn merge_users(
+    conn: &mut MysqlConnection,
+    keep_id: &str,
+    drop_id: &str,
+) -> Result<MergeSummary, CliError> {
+    use crate::schema::MatchUser;
+    use crate::schema::TournamentResult;
+    use crate::schema::User;
+    use crate::schema::UserAlias;
+    use diesel::sql_types::Varchar;
+
+    let users = get_users(conn, &[keep_id.to_owned(), drop_id.to_owned()])?;
+    let keep = users
+        .iter()
+        .find(|u| u.userId == keep_id)
+        .ok_or_else(|| CliError::Usage(format!("No user with id {}", keep_id)))?;
+    let dropped = users
+        .iter()
+        .find(|u| u.userId == drop_id)
+        .ok_or_else(|| CliError::Usage(format!("No user with id {}", drop_id)))?;
+    ensure_no_shared_tournaments(conn, keep_id, drop_id)?;
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let match_users = diesel::update(MatchUser::table.filter(MatchUser::userId.eq(drop_id)))
+            .set(MatchUser::userId.eq(keep_id))
+            .execute(conn)?;
+
+        // Tournaments both accounts played in would end up linked twice
+        diesel::sql_query(
+            "DELETE d FROM _TournamentToUser d \
+             JOIN _TournamentToUser k ON k.A = d.A AND k.B = ? \
+             WHERE d.B = ?",
+        )
+        .bind::<Varchar, _>(keep_id)
+        .bind::<Varchar, _>(drop_id)
+        .execute(conn)?;
+        let tournament_users = diesel::sql_query("UPDATE _TournamentToUser SET B = ? WHERE B = ?")
+            .bind::<Varchar, _>(keep_id)
+            .bind::<Varchar, _>(drop_id)
+            .execute(conn)?;
+        let tournament_results =
+            diesel::update(TournamentResult::table.filter(TournamentResult::userId.eq(drop_id)))
+                .set(TournamentResult::userId.eq(keep_id))
+                .execute(conn)?;
+
+        let mut user_aliases =
+            diesel::update(UserAlias::table.filter(UserAlias::userId.eq(drop_id)))
+                .set(UserAlias::userId.eq(keep_id))
+                .execute(conn)?;
+        if dropped.username != keep.username {
+            user_aliases += diesel::insert_into(UserAlias::table)
+                .values(&NewUserAlias {
+                    userId: keep_id.to_owned(),
+                    username: dropped.username.to_owned(),
+                    changedAt: Utc::now().naive_utc(),
+                })
+                .execute(conn)?;
+        }
+
+        diesel::update(User::table.filter(User::userId.eq(keep_id)))
+            .set((
+                User::wins.eq(User::wins + dropped.wins),
+                User::ranking.eq(User::ranking + dropped.ranking),
+            ))
+            .execute(conn)?;
+        diesel::delete(User::table.filter(User::userId.eq(drop_id))).execute(conn)?;
+
+        Ok(MergeSummary {
+            match_users,
+            tournament_users,
+            tournament_results,
+            user_aliases,
+            wins: dropped.wins,
+            ranking: dropped.ranking,
+        })
+    })
+    .db_context("Failed to merge users, nothing was changed")
+}
+
+/// Fails when both accounts played the same tournament, they'd be placed twice in it
+/// and every recompute would count it twice
+pub fn ensure_no_shared_tournaments(
+    conn: &mut MysqlConnection,
+    keep_id: &str,
+    drop_id: &str,
+) -> Result<(), CliError> {
+    let kept = played_tournament_ids(conn, keep_id)?;
+    let shared: Vec<String> = played_tournament_ids(conn, drop_id)?
+        .into_iter()
+        .filter(|id| kept.contains(id))
+        .map(|id| id.to_string())
+        .collect();
+    if !shared.is_empty() {
+        return Err(CliError::Usage(format!(
+            "Both players played tournament {}, they're different players or one of those \
+             tournaments has to be fixed first",
+            shared.join(", ")
+        )));
     }
-    create_tournament_user_link(conn, linked_tournament_users);
+
+    Ok(())
+}
+
+fn played_tournament_ids(conn: &mut MysqlConnection, user_id: &str) -> Result<Vec<i32>, CliError> {
+    use crate::schema::MatchUser;
+    use crate::schema::Match_;
+
+    let ids: Vec<Option<i32>> = MatchUser::table
+        .inner_join(Match_::table)
+        .filter(MatchUser::userId.eq(user_id))
+        .select(Match_::tournamentId)
+        .distinct()
+        .load(conn)
+        .db_context("Failed to get the user's tournaments")?;
+
+    Ok(ids.into_iter().flatten().collect())
 }
 
-fn create_tournament(conn: &mut MysqlConnection, new: NewTournament) -> Tournament {
+pub fn list_tournaments(
+    conn: &mut MysqlConnection,
+    limit: i64,
+) -> Result<Vec<Tournament>, CliError> {
+    use crate::schema::Tournament;
+
+    Tournament::table
+        .order(Tournament::date.desc())
+        .limit(limit)
+        .select(models::Tournament::as_select())
+        .load(conn)
+        .db_context("Failed to list tournaments")
+}
+
+pub fn get_leaderboard(conn: &mut MysqlConnection, limit: i64) -> Result<Vec<User>, CliError> {
+    use crate::schema::User;
+
+    User::table
+        .order((User::ranking.desc(), User::wins.desc()))
+        .limit(limit)
+        .select(models::User::as_select())
+        .load(conn)
+        .db_context("Failed to get leaderboard")
+}
+
+/// Users with the highest total of a stat over all their matches, or those of one season
+pub fn get_stat_leaderboard(
+    conn: &mut MysqlConnection,
+    stat: &str,
+    season_id: Option<i32>,
+    limit: i64,
+) -> Result<Vec<models::StatTotal>, CliError> {
+    use diesel::sql_types::{BigInt, Integer, Nullable, Varchar};
+
+    diesel::sql_query(
+        "SELECT u.userId, u.username, CAST(SUM(s.value) AS SIGNED) AS total \
+         FROM MatchUserStat s \
+         JOIN MatchUser mu ON mu.id = s.matchUserId \
+         JOIN User u ON u.userId = mu.userId \
+         JOIN `Match` m ON m.id = mu.matchId \
+         JOIN Tournament t ON t.id = m.tournamentId \
+         WHERE s.name = ? AND (? IS NULL OR t.seasonId = ?) \
+         GROUP BY u.userId, u.username \
+         ORDER BY total DESC \
+         LIMIT ?",
+    )
+    .bind::<Varchar, _>(stat)
+    .bind::<Nullable<Integer>, _>(season_id)
+    .bind::<Nullable<Integer>, _>(season_id)
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+    .db_context("Failed to get stat leaderboard")
+}
+
+/// Points & wins per user over the stored results of a season's tournaments
+pub fn get_season_leaderboard(
+    conn: &mut MysqlConnection,
+    season_id: i32,
+    limit: i64,
+) -> Result<Vec<models::SeasonStanding>, CliError> {
+    use diesel::sql_types::{BigInt, Integer};
+
+    diesel::sql_query(
+        "SELECT u.userId, u.username, \
+         CAST(SUM(r.points + r.statPoints) AS SIGNED) AS points, \
+         CAST(COALESCE(SUM(r.placement = 1), 0) AS SIGNED) AS wins, \
+         COUNT(DISTINCT r.tournamentId) AS tournaments \
+         FROM TournamentResult r \
+         JOIN Tournament t ON t.id = r.tournamentId \
+         JOIN User u ON u.userId = r.userId \
+         WHERE t.seasonId = ? \
+         GROUP BY u.userId, u.username \
+         ORDER BY points DESC, wins DESC \
+         LIMIT ?",
+    )
+    .bind::<Integer, _>(season_id)
+    .bind::<BigInt, _>(limit)
+    .load(conn)
+    .db_context("Failed to get season leaderboard")
+}
+
+pub fn get_seasons(conn: &mut MysqlConnection) -> Result<Vec<Season>, CliError> {
+    use crate::schema::Season;
+
+    Season::table
+        .order(Season::startsAt.desc())
+        .select(models::Season::as_select())
+        .load(conn)
+        .db_context("Failed to get seasons")
+}
+
+pub fn get_season(conn: &mut MysqlConnection, name: &str) -> Result<Option<Season>, CliError> {
+    use crate::schema::Season;
+
+    Season::table
+        .filter(Season::name.eq(name))
+        .select(models::Season::as_select())
+        .first(conn)
+        .optional()
+        .db_context("Failed to get season")
+}
+
+/// Ids of the tournaments of a season, oldest first
+pub fn get_season_tournament_ids(
+    conn: &mut MysqlConnection,
+    season_id: i32,
+) -> Result<Vec<i32>, CliError> {
+    use crate::schema::Tournament;
+
+    Tournament::table
+        .filter(Tournament::seasonId.eq(season_id))
+        .order(Tournament::date.asc())
+        .select(Tournament::id)
+        .load(conn)
+        .db_context("Failed to get season tournaments")
+}
+
+/// Creates a season and files the existing tournaments without one that fall in it.
+/// Seasons can't overlap so every date belongs to at most one. Returns the amount filed
+pub fn create_season(
+    conn: &mut MysqlConnection,
+    new_season: NewSeason,
+) -> Result<(Season, usize), CliError> {
+    use crate::schema::Season;
+    use crate::schema::Tournament;
+
+    if get_season(conn, &new_season.name)?.is_some() {
+        return Err(CliError::Usage(format!(
+            "A season named '{}' already exists",
+            new_season.name
+        )));
+    }
+    let overlapping: Option<models::Season> = Season::table
+        .filter(Season::startsAt.le(new_season.endsAt))
+        .filter(Season::endsAt.ge(new_season.startsAt))
+        .select(models::Season::as_select())
+        .first(conn)
+        .optional()
+        .db_context("Failed to look up overlapping seasons")?;
+    if let Some(overlapping) = overlapping {
+        return Err(CliError::Usage(format!(
+            "Season '{}' overlaps with '{}' ({} to {})",
+            new_season.name,
+            overlapping.name,
+            overlapping.startsAt.format("%Y-%m-%d"),
+            overlapping.endsAt.format("%Y-%m-%d")
+        )));
+    }
+
+    conn.transaction(|conn| {
+        diesel::insert_into(Season::table)
+            .values(&new_season)
+            .execute(conn)?;
+        let season = Season::table
+            .filter(Season::name.eq(&new_season.name))
+            .select(models::Season::as_select())
+            .first(conn)?;
+
+        let tournaments = diesel::update(
+            Tournament::table
+                .filter(Tournament::seasonId.is_null())
+                .filter(Tournament::date.ge(season.startsAt))
+                .filter(Tournament::date.le(season.endsAt)),
+        )
+        .set(Tournament::seasonId.eq(season.id))
+        .execute(conn)?;
+
+        Ok((season, tournaments))
+    })
+    .db_context("Failed to create season, nothing was changed")
+}
+
+pub fn get_tournament_results(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<Vec<models::TournamentResult>, CliError> {
+    use crate::schema::TournamentResult;
+
+    TournamentResult::table
+        .filter(TournamentResult::tournamentId.eq(tournament_id))
+        .order(TournamentResult::id.asc())
+        .select(models::TournamentResult::as_select())
+        .load(conn)
+        .db_context("Failed to get tournament results")
+}
+
+/// Ids of the tournaments whose results were applied, live tournaments only get their
+/// player links once they're complete
+pub fn get_finished_tournament_ids(conn: &mut MysqlConnection) -> Result<Vec<i32>, CliError> {
+    let links: Vec<models::TournamentToUser> =
+        diesel::sql_query("SELECT A, B FROM _TournamentToUser ORDER BY A")
+            .load(conn)
+            .db_context("Failed to get tournament to user links")?;
+
+    let mut ids: Vec<i32> = links.iter().map(|link| link.tournament_id).collect();
+    ids.dedup();
+
+    Ok(ids)
+}
+
+/// Fails for a finished tournament that was uploaded before results were stored,
+/// its points can't be taken back off exactly until `recompute` stored them
+pub fn ensure_results_stored(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<(), CliError> {
+    if count_tournament_results(conn, tournament_id)? == 0
+        && get_finished_tournament_ids(conn)?.contains(&tournament_id)
+    {
+        return Err(CliError::Usage(format!(
+            "Tournament {} was uploaded before results were stored, run `pbt-cli recompute` \
+             first so its points can be taken off exactly",
+            tournament_id
+        )));
+    }
+
+    Ok(())
+}
+
+/// Fails for a tournament whose results were already applied, finishing it again would
+/// count its wins & ranking points twice
+pub fn ensure_not_finished(conn: &mut MysqlConnection, tournament_id: i32) -> Result<(), CliError> {
+    if count_tournament_results(conn, tournament_id)? > 0
+        || get_finished_tournament_ids(conn)?.contains(&tournament_id)
+    {
+        return Err(CliError::Usage(format!(
+            "Tournament {} is already finished, its results were applied",
+            tournament_id
+        )));
+    }
+
+    Ok(())
+}
+
+fn count_tournament_results(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<i64, CliError> {
+    use crate::schema::TournamentResult;
+
+    TournamentResult::table
+        .filter(TournamentResult::tournamentId.eq(tournament_id))
+        .count()
+        .get_result(conn)
+        .db_context("Failed to count tournament results")
+}
+
+/// Rows changed by `delete_tournament`
+#[derive(Serialize, Debug)]
+pub struct DeleteSummary {
+    pub matches: usize,
+    pub tournament_users: usize,
+    pub tournament_results: usize,
+}
+
+/// Deletes a tournament with everything of it and takes its stored results back off the
+/// users' wins & rankings, all or nothing. Refused without stored results, see
+/// `ensure_results_stored`
+pub fn delete_tournament(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<DeleteSummary, CliError> {
+    use crate::schema::Tournament;
+    use crate::schema::TournamentResult;
+    use diesel::sql_types::Integer;
+
+    conn.transaction::<_, CliError, _>(|conn| {
+        ensure_results_stored(conn, tournament_id)?;
+        let results = get_tournament_results(conn, tournament_id)?;
+        for result in &results {
+            let wins = i32::from(result.placement == Some(1));
+            update_user_counters(
+                conn,
+                &result.userId,
+                -wins,
+                -(result.points + result.statPoints),
+            )?;
+        }
+        diesel::delete(
+            TournamentResult::table.filter(TournamentResult::tournamentId.eq(tournament_id)),
+        )
+        .execute(conn)
+        .db_context("Failed to delete tournament results")?;
+
+        let tournament_users = diesel::sql_query("DELETE FROM _TournamentToUser WHERE A = ?")
+            .bind::<Integer, _>(tournament_id)
+            .execute(conn)
+            .db_context("Failed to delete tournament to user links")?;
+        let matches = delete_tournament_matches(conn, tournament_id)?;
+        diesel::delete(Tournament::table.find(tournament_id))
+            .execute(conn)
+            .db_context("Failed to delete tournament")?;
+
+        Ok(DeleteSummary {
+            matches,
+            tournament_users,
+            tournament_results: results.len(),
+        })
+    })
+}
+
+/// Rows changed by `recompute_results`
+#[derive(Serialize, Debug)]
+pub struct RecomputeSummary {
+    pub tournaments: usize,
+    pub tournament_results: usize,
+    pub users: usize,
+}
+
+/// Wins & ranking per user summed from the stored results
+const RESULT_TOTALS: &str = "( \
+    SELECT userId, SUM(placement = 1) AS wins, SUM(points + statPoints) AS ranking \
+    FROM TournamentResult GROUP BY userId \
+)";
+
+/// Finished tournaments without stored results, uploaded before results were stored.
+/// `recompute` works theirs out from the matches with the current scoring
+pub fn get_tournament_ids_without_results(
+    conn: &mut MysqlConnection,
+) -> Result<Vec<i32>, CliError> {
+    use crate::schema::TournamentResult;
+
+    let with_results: Vec<i32> = TournamentResult::table
+        .select(TournamentResult::tournamentId)
+        .distinct()
+        .load(conn)
+        .db_context("Failed to get tournament results")?;
+
+    Ok(get_finished_tournament_ids(conn)?
+        .into_iter()
+        .filter(|id| !with_results.contains(id))
+        .collect())
+}
+
+/// Users whose wins or ranking differ from the totals of their stored results, like after
+/// a manual change or from tournaments without stored results. `recompute` resets them
+pub fn count_users_off_results(conn: &mut MysqlConnection) -> Result<i64, CliError> {
+    let count: models::Count = diesel::sql_query(format!(
+        "SELECT COUNT(*) AS count FROM User u LEFT JOIN {} r ON r.userId = u.userId \
+         WHERE u.wins <> COALESCE(r.wins, 0) OR u.ranking <> COALESCE(r.ranking, 0)",
+        RESULT_TOTALS
+    ))
+    .get_result(conn)
+    .db_context("Failed to compare users with their results")?;
+
+    Ok(count.count)
+}
+
+/// Stores the results of the given finished tournaments again with the current scoring,
+/// then sets every user's wins & ranking to the totals of all stored results.
+/// All or nothing
+pub fn recompute_results(
+    conn: &mut MysqlConnection,
+    tournaments: &[(i32, ParsedTournament)],
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Result<RecomputeSummary, CliError> {
+    use crate::schema::TournamentResult;
+
+    conn.transaction::<_, CliError, _>(|conn| {
+        let mut written = 0;
+        for (tournament_id, parsed_tournament) in tournaments {
+            let results =
+                tournament_results(*tournament_id, parsed_tournament, scoring, stat_points)
+                    .map_err(|error| {
+                        CliError::Parse(format!(
+                            "Tournament {}: {}",
+                            tournament_id,
+                            error.message()
+                        ))
+                    })?;
+
+            diesel::delete(
+                TournamentResult::table.filter(TournamentResult::tournamentId.eq(tournament_id)),
+            )
+            .execute(conn)
+            .db_context("Failed to delete tournament results")?;
+            written += diesel::insert_into(TournamentResult::table)
+                .values(&results)
+                .execute(conn)
+                .db_context("Failed to insert tournament results")?;
+        }
+
+        let users = diesel::sql_query(format!(
+            "UPDATE User u LEFT JOIN {} r ON r.userId = u.userId \
+             SET u.wins = COALESCE(r.wins, 0), u.ranking = COALESCE(r.ranking, 0)",
+            RESULT_TOTALS
+        ))
+        .execute(conn)
+        .db_context("Failed to update user wins & rankings")?;
+
+        Ok(RecomputeSummary {
+            tournaments: tournaments.len(),
+            tournament_results: written,
+            users,
+        })
+    })
+}
+
+fn create_tournament(
+    conn: &mut MysqlConnection,
+    new: NewTournament,
+) -> Result<Tournament, CliError> {
     use crate::schema::Tournament;
 
     conn.transaction(|conn| {
@@ -123,83 +1289,208 @@ fn create_tournament(conn: &mut MysqlConnection, new: NewTournament) -> Tourname
             .select(models::Tournament::as_select())
             .first(conn)
     })
-    .expect("Failed to create new tournament")
+    .db_context("Failed to create new tournament")
 }
 
-fn create_matches(conn: &mut MysqlConnection, matches: Vec<(NewMatch, Vec<NewMatchUser>)>) {
+/// Everything inserted for one match
+struct MatchRows {
+    new_match: NewMatch,
+    teams: Vec<models::MatchTeam>,
+    users: Vec<NewMatchUser>,
+    stats: Vec<NewMatchUserStat>,
+}
+
+fn create_matches(conn: &mut MysqlConnection, matches: Vec<MatchRows>) -> Result<(), CliError> {
+    use crate::schema::MatchTeam;
     use crate::schema::MatchUser;
+    use crate::schema::MatchUserStat;
     use crate::schema::Match_;
 
-    for (new_match, match_users) in matches {
+    for m in matches {
         diesel::insert_into(Match_::table)
-            .values(&new_match)
+            .values(&m.new_match)
             .execute(conn)
-            .expect("Failed to insert new match");
+            .db_context("Failed to insert new match")?;
+
+        if !m.teams.is_empty() {
+            diesel::insert_into(MatchTeam::table)
+                .values(&m.teams)
+                .execute(conn)
+                .db_context("Failed to insert new match teams")?;
+        }
 
         diesel::insert_into(MatchUser::table)
-            .values(&match_users)
+            .values(&m.users)
             .execute(conn)
-            .expect("Failed to insert new match users");
+            .db_context("Failed to insert new match users")?;
+
+        if !m.stats.is_empty() {
+            diesel::insert_into(MatchUserStat::table)
+                .values(&m.stats)
+                .execute(conn)
+                .db_context("Failed to insert new match user stats")?;
+        }
     }
+
+    Ok(())
 }
 
-fn create_users(conn: &mut MysqlConnection, users: Vec<NewUser>) {
+/// Deletes the matches of a tournament with their teams, match users & stats
+fn delete_tournament_matches(
+    conn: &mut MysqlConnection,
+    tournament_id: i32,
+) -> Result<usize, CliError> {
+    use crate::schema::MatchTeam;
+    use crate::schema::MatchUser;
+    use crate::schema::Match_;
+
+    let match_ids: Vec<String> = Match_::table
+        .filter(Match_::tournamentId.eq(tournament_id))
+        .select(Match_::id)
+        .load(conn)
+        .db_context("Failed to get tournament matches")?;
+
+    // Stats go with their match user
+    diesel::delete(MatchUser::table.filter(MatchUser::matchId.eq_any(&match_ids)))
+        .execute(conn)
+        .db_context("Failed to delete match users")?;
+    diesel::delete(MatchTeam::table.filter(MatchTeam::matchId.eq_any(&match_ids)))
+        .execute(conn)
+        .db_context("Failed to delete match teams")?;
+    diesel::delete(Match_::table.filter(Match_::id.eq_any(&match_ids)))
+        .execute(conn)
+        .db_context("Failed to delete matches")
+}
+
+/// Creates the missing users & updates the usernames of existing ones, keeping the previous
+/// name in `UserAlias`, see `renames_user`. Returns how many were created & renamed.
+fn create_users(
+    conn: &mut MysqlConnection,
+    users: Vec<NewUser>,
+    tournament_id: i32,
+    date: NaiveDateTime,
+) -> Result<(usize, usize), CliError> {
     use crate::schema::User;
+    use crate::schema::UserAlias;
+
+    let user_ids: Vec<String> = users.iter().map(|u| u.userId.to_owned()).collect();
+    let existing = get_users(conn, &user_ids)?;
+    let latest = latest_tournament_dates(conn, &user_ids, Some(tournament_id))?;
 
+    let mut created = 0;
+    let mut renamed = 0;
     for new_user in users {
-        diesel::insert_into(User::table)
-            .values(&new_user)
-            .on_conflict_do_nothing()
+        let previous = existing.iter().find(|u| u.userId == new_user.userId);
+        let previous = match previous {
+            Some(previous) => previous,
+            None => {
+                created += diesel::insert_into(User::table)
+                    .values(&new_user)
+                    .on_conflict_do_nothing()
+                    .execute(conn)
+                    .db_context("Failed to insert new user")?;
+                continue;
+            }
+        };
+
+        if !renames_user(previous, &new_user.username, &latest, date) {
+            continue;
+        }
+
+        diesel::insert_into(UserAlias::table)
+            .values(&NewUserAlias {
+                userId: previous.userId.to_owned(),
+                username: previous.username.to_owned(),
+                changedAt: Utc::now().naive_utc(),
+            })
             .execute(conn)
-            .expect("Failed to insert new user");
+            .db_context("Failed to insert user alias")?;
 
         diesel::update(User::table)
             .filter(User::userId.eq(&new_user.userId))
             .set(User::username.eq(new_user.username))
             .execute(conn)
-            .expect("Failed to update user: username");
+            .db_context("Failed to update user: username")?;
+        renamed += 1;
     }
+
+    Ok((created, renamed))
 }
 
-fn update_user_ranks(conn: &mut MysqlConnection, users: Vec<MatchUser>) {
-    use crate::schema::User;
+/// Whether writing a tournament dated `date` changes `user`'s name to `username`. Users who
+/// played a tournament at or after `date` keep their name, so uploading an older tournament
+/// doesn't undo a rename. `latest` comes from `latest_tournament_dates`
+pub fn renames_user(
+    user: &User,
+    username: &str,
+    latest: &HashMap<String, NaiveDateTime>,
+    date: NaiveDateTime,
+) -> bool {
+    user.username != username && latest.get(&user.userId).is_none_or(|latest| *latest < date)
+}
 
-    for i in 0..users.len() {
-        let user = &users[i];
-        diesel::update(User::table)
-            .filter(User::userId.eq(&user.user_id))
-            .set(User::ranking.eq(User::ranking + (users.len() - i) as i32))
-            .execute(conn)
-            .expect(&format!(
-                "Failed to update user ranking: ({})",
-                user.username
-            ));
-    }
+/// Date of the latest tournament each user played, other than `except` (the one being written)
+pub fn latest_tournament_dates(
+    conn: &mut MysqlConnection,
+    user_ids: &[String],
+    except: Option<i32>,
+) -> Result<HashMap<String, NaiveDateTime>, CliError> {
+    use crate::schema::MatchUser;
+    use crate::schema::Match_;
+    use crate::schema::Tournament;
+
+    let dates: Vec<(String, Option<NaiveDateTime>)> = MatchUser::table
+        .inner_join(Match_::table.inner_join(Tournament::table))
+        .filter(MatchUser::userId.eq_any(user_ids))
+        .filter(Tournament::id.ne_all(except))
+        .group_by(MatchUser::userId)
+        .select((MatchUser::userId, diesel::dsl::max(Tournament::date)))
+        .load(conn)
+        .db_context("Failed to get users' latest tournaments")?;
+
+    Ok(dates
+        .into_iter()
+        .filter_map(|(user_id, date)| Some((user_id, date?)))
+        .collect())
 }
 
-fn update_user_win(conn: &mut MysqlConnection, user: MatchUser) {
+/// Adds to a user's wins & ranking, negative amounts take a tournament's results back off
+fn update_user_counters(
+    conn: &mut MysqlConnection,
+    user_id: &str,
+    wins: i32,
+    ranking: i32,
+) -> Result<(), CliError> {
     use crate::schema::User;
 
     diesel::update(User::table)
-        .filter(User::userId.eq(user.user_id))
-        .set(User::wins.eq(User::wins + 1))
+        .filter(User::userId.eq(user_id))
+        .set((
+            User::wins.eq(User::wins + wins),
+            User::ranking.eq(User::ranking + ranking),
+        ))
         .execute(conn)
-        .expect(&format!("Failed to update user wins: ({})", user.username));
+        .db_context(&format!(
+            "Failed to update user wins & ranking: ({})",
+            user_id
+        ))?;
+
+    Ok(())
 }
 
-fn create_tournament_user_link(conn: &mut MysqlConnection, linked: Vec<(i32, String)>) {
+pub fn create_tournament_user_link(
+    conn: &mut MysqlConnection,
+    linked: Vec<(i32, String)>,
+) -> QueryResult<()> {
     // have to do raw sql query since diesel dont work with non-primary key tables and this table is only for internal prisma
+    use diesel::sql_types::{Integer, Varchar};
 
-    let mut batch_query: String = String::new();
-    for (a, b) in linked {
-        let query = format!(
-            "INSERT INTO _TournamentToUser (A, B) VALUES ({}, '{}');",
-            a, b
-        );
-
-        batch_query += &query;
+    for (tournament_id, user_id) in linked {
+        diesel::sql_query("INSERT INTO _TournamentToUser (A, B) VALUES (?, ?)")
+            .bind::<Integer, _>(tournament_id)
+            .bind::<Varchar, _>(user_id)
+            .execute(conn)?;
     }
 
-    conn.batch_execute(&batch_query)
-        .expect("Failed to batch insert tournament to user fields");
+    Ok(())
 }
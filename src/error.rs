@@ -0,0 +1,81 @@
+use std::fmt;
+
+/// Failure classes, each with its own process exit code so scripts can tell them apart
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad arguments or input values
+    Usage(String),
+    /// Missing or invalid config file / profile
+    Config(String),
+    /// The tournament file (or archive) has invalid content
+    Parse(String),
+    /// Couldn't connect to the database
+    Connection(String),
+    /// A query failed after connecting
+    Database(String),
+    /// Reading or writing a local file failed
+    Io(String),
+    /// The user declined a confirmation
+    Cancelled(String),
+}
+
+impl CliError {
+    pub fn exit_code(&self) -> u8 {
+        match self {
+            CliError::Usage(_) => 2,
+            CliError::Config(_) => 3,
+            CliError::Parse(_) => 4,
+            CliError::Connection(_) => 5,
+            CliError::Database(_) => 6,
+            CliError::Io(_) => 7,
+            CliError::Cancelled(_) => 8,
+        }
+    }
+
+    pub fn to_str(&self) -> &'static str {
+        match self {
+            CliError::Usage(_) => "Usage Error",
+            CliError::Config(_) => "Config Error",
+            CliError::Parse(_) => "Parse Error",
+            CliError::Connection(_) => "Connection Error",
+            CliError::Database(_) => "Database Error",
+            CliError::Io(_) => "IO Error",
+            CliError::Cancelled(_) => "Cancelled",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            CliError::Usage(message)
+            | CliError::Config(message)
+            | CliError::Parse(message)
+            | CliError::Connection(message)
+            | CliError::Database(message)
+            | CliError::Io(message)
+            | CliError::Cancelled(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.to_str(), self.message())
+    }
+}
+
+impl From<diesel::result::Error> for CliError {
+    fn from(error: diesel::result::Error) -> Self {
+        CliError::Database(error.to_string())
+    }
+}
+
+/// Adds context to database errors, `.db_context("Failed to insert match")?`
+pub trait DbContext<T> {
+    fn db_context(self, context: &str) -> Result<T, CliError>;
+}
+
+impl<T> DbContext<T> for diesel::QueryResult<T> {
+    fn db_context(self, context: &str) -> Result<T, CliError> {
+        self.map_err(|error| CliError::Database(format!("{}, {}", context, error)))
+    }
+}
@@ -2,6 +2,9 @@ use chrono::{NaiveDateTime, Utc};
 use colored::Colorize;
 use std::io;
 
+use crate::config::PromptDefaults;
+use crate::error::CliError;
+
 const DATEINPUT_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 #[derive(Debug)]
@@ -9,13 +12,46 @@ pub struct UserInput {
     pub title: String,
     pub date: NaiveDateTime,
     pub link: Option<String>,
+    /// Only set by flags, prompting leaves the season to the tournament date
+    pub season: Option<String>,
+}
+
+/// Builds the input from command line flags instead of prompting
+pub fn from_flags(
+    defaults: &PromptDefaults,
+    title: String,
+    date: Option<&str>,
+    link: Option<String>,
+) -> Result<UserInput, CliError> {
+    let date = match date {
+        Some(date) => parse_date(date).map_err(|error| {
+            CliError::Usage(format!(
+                "Failed to parse date '{}' ({:?}), expected YYYY-MM-DD HH:MM",
+                date,
+                error.kind()
+            ))
+        })?,
+        None => Utc::now().naive_local(),
+    };
+
+    Ok(UserInput {
+        title,
+        date,
+        link: link.or(defaults.link.to_owned()),
+        season: None,
+    })
+}
+
+pub fn parse_date(date: &str) -> chrono::ParseResult<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date, DATEINPUT_FORMAT)
 }
 
-pub fn get_user_inputs() -> UserInput {
+pub fn get_user_inputs(defaults: &PromptDefaults) -> UserInput {
     UserInput {
-        title: get_title(),
+        title: get_title(&defaults.title),
         date: get_date(),
-        link: get_optional_link(),
+        link: get_optional_link(&defaults.link),
+        season: None,
     }
 }
 
@@ -24,20 +60,30 @@ fn get_stdin(mut out: String) -> String {
         .read_line(&mut out)
         .expect("Failed to read line");
 
-    return out.trim_end().to_string();
+    out.trim_end().to_string()
 }
 
-fn get_title() -> String {
+fn get_title(default: &Option<String>) -> String {
     println!("{}{}", "◆".green(), " Tournament Title:".white());
+    if let Some(default) = default {
+        println!(
+            "{}",
+            format!("(Leave Empty For \"{}\")", default).bright_black()
+        );
+    }
 
     let title = get_stdin(String::new());
 
     if title.is_empty() {
+        if let Some(default) = default {
+            return default.to_owned();
+        }
+
         println!("{}", "No Title, Try Again!".red().bold());
-        return get_title();
+        return get_title(default);
     }
 
-    return title;
+    title
 }
 
 fn get_date() -> NaiveDateTime {
@@ -55,9 +101,9 @@ fn get_date() -> NaiveDateTime {
         return Utc::now().naive_local();
     }
 
-    let naive_date = NaiveDateTime::parse_from_str(&date_string, DATEINPUT_FORMAT);
+    let naive_date = parse_date(&date_string);
 
-    let date = match naive_date {
+    match naive_date {
         Ok(date) => date,
         Err(error) => {
             println!(
@@ -68,21 +114,64 @@ fn get_date() -> NaiveDateTime {
             );
             get_date()
         }
-    };
-
-    return date;
+    }
 }
 
-fn get_optional_link() -> Option<String> {
+fn get_optional_link(default: &Option<String>) -> Option<String> {
     println!(
         "{}{}",
         "◆".green(),
         " Tournament Media Link (Optional):".white()
     );
+    if let Some(default) = default {
+        println!(
+            "{}",
+            format!("(Leave Empty For \"{}\")", default).bright_black()
+        );
+    }
 
     let link = get_stdin(String::new());
 
-    let result: Option<String> = if link.is_empty() { None } else { Some(link) };
+    let result: Option<String> = if link.is_empty() {
+        default.to_owned()
+    } else {
+        Some(link)
+    };
 
     result
 }
+
+pub fn confirm(question: &str) -> bool {
+    println!(
+        "{}{}{}",
+        "◆ ".green(),
+        question.white(),
+        " [y/N]".bright_black()
+    );
+
+    let answer = get_stdin(String::new());
+
+    matches!(answer.trim().to_lowercase().as_str(), "y" | "yes")
+}
+
+/// Asks to pick one of the options, `None` when skipped
+pub fn choose(question: &str, options: &[String]) -> Option<usize> {
+    println!("{}{}", "◆ ".green(), question.white());
+    for (i, option) in options.iter().enumerate() {
+        println!("{}{}", format!("  {}) ", i + 1).bright_black(), option);
+    }
+    println!("{}", "(Leave Empty To Skip)".bright_black());
+
+    let answer = get_stdin(String::new());
+    if answer.is_empty() {
+        return None;
+    }
+
+    match answer.trim().parse::<usize>() {
+        Ok(choice) if choice >= 1 && choice <= options.len() => Some(choice - 1),
+        _ => {
+            println!("{}", "Not A Valid Choice, Try Again!".red().bold());
+            choose(question, options)
+        }
+    }
+}
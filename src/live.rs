@@ -0,0 +1,46 @@
+use std::{fs, path::Path, time::SystemTime};
+
+use crate::bracket;
+use crate::error::CliError;
+use crate::tournament_parser::{LifeStatus, ParsedTournament};
+
+/// Modification time & size of a file, a change in either means it was written to
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FileStamp {
+    modified: Option<SystemTime>,
+    len: u64,
+}
+
+pub fn file_stamp(path: &Path) -> Result<FileStamp, CliError> {
+    let metadata = fs::metadata(path)
+        .map_err(|error| CliError::Io(format!("Failed to read {}, {}", path.display(), error)))?;
+
+    Ok(FileStamp {
+        modified: metadata.modified().ok(),
+        len: metadata.len(),
+    })
+}
+
+/// Players & teams still marked as `playing`
+pub fn playing_count(tournament: &ParsedTournament) -> usize {
+    tournament
+        .matches
+        .iter()
+        .map(|m| {
+            m.players
+                .iter()
+                .filter(|p| p.life_status == LifeStatus::Playing)
+                .count()
+                + m.teams
+                    .iter()
+                    .filter(|t| t.life_status == LifeStatus::Playing)
+                    .count()
+        })
+        .sum()
+}
+
+/// A tournament is complete once nobody is playing and the bracket checks pass,
+/// which includes the final round being present with a single alive winner
+pub fn is_complete(tournament: &ParsedTournament) -> bool {
+    playing_count(tournament) == 0 && bracket::check(tournament).is_empty()
+}
@@ -1,85 +1,193 @@
 use std::{
-    env, thread,
+    env,
+    io::{self, IsTerminal},
+    process::ExitCode,
+    thread,
     time::{self, Instant},
 };
 
-use crate::input::UserInput;
+use crate::cli::{Cli, Command, PlayerCommand, SeasonCommand};
+use crate::error::CliError;
+use crate::output::OutputMode;
 
-use colored::{ColoredString, Colorize};
+use clap::Parser;
+use colored::Colorize;
+use serde_json::Value;
 
+mod audit;
+mod backup;
+mod bracket;
+mod cli;
+mod commands;
+mod config;
 mod db;
+mod error;
 mod input;
+mod live;
+mod output;
+mod preview;
+mod report;
+mod resolve;
+mod rounds;
+mod season;
+mod server;
+mod steam_id;
 mod tournament_parser;
+mod watch;
+mod webhook;
 
 mod models;
 mod schema;
 
-const CMD_PROCESSING_STEPS: i32 = 6;
-
-fn cmd_step(msg: ColoredString, step: i32) {
-    println!(
-        "{}{}{}{}",
-        "[".bright_black(),
-        format!("{step}/{CMD_PROCESSING_STEPS}").white(),
-        "] ".bright_black(),
-        msg
-    );
-}
-
 fn wait(sec: f32) {
     let duration_sec = time::Duration::from_secs_f32(sec);
     thread::sleep(duration_sec);
 }
 
-fn main() {
-    let args: Vec<String> = env::args().collect();
-    let tournament_file = args.get(1).expect("No Tournament File");
+/// A console that only has this process attached was created for it,
+/// meaning the binary was double-clicked (or had a file dropped on it) instead of run from a terminal
+#[cfg(windows)]
+fn launched_by_double_click() -> bool {
+    use windows_sys::Win32::System::Console::GetConsoleProcessList;
+
+    let mut processes = [0u32; 2];
+    let count = unsafe { GetConsoleProcessList(processes.as_mut_ptr(), processes.len() as u32) };
+
+    count == 1
+}
+
+#[cfg(not(windows))]
+fn launched_by_double_click() -> bool {
+    false
+}
+
+fn main() -> ExitCode {
+    let args = cli::with_default_subcommand(env::args().collect());
+    let cli = match Cli::try_parse_from(&args) {
+        Ok(cli) => cli,
+        Err(error) if cli::wants_json(&args) && error.use_stderr() => {
+            output::set_mode(OutputMode::Json);
+            let error = CliError::Usage(error.render().to_string().trim().to_owned());
+            output::error(&error);
+            return ExitCode::from(error.exit_code());
+        }
+        Err(error) => error.exit(),
+    };
+    output::set_mode(cli.output);
     let run_type: &str = match cfg!(debug_assertions) {
         true => "Debug",
         false => "Release",
     };
 
-    println!(
+    output::line(format!(
         "{}{}",
         "<- Party Bots Tournament Upload CLI ->\n".blue().bold(),
         format!("Written In Rust, By @Olof | {}\n", run_type)
             .bright_black()
             .italic()
-    );
-
-    let user_input: UserInput = input::get_user_inputs();
+    ));
 
     let elapsed_start = Instant::now();
+    let result = run(&cli);
+
+    let exit_code = match result {
+        Ok(data) => {
+            output::result(
+                cli.command.name(),
+                data,
+                elapsed_start.elapsed().as_millis() as f32 / 1000.0,
+            );
+            ExitCode::SUCCESS
+        }
+        Err(error) => {
+            output::error(&error);
+            ExitCode::from(error.exit_code())
+        }
+    };
 
-    cmd_step("Parsing Tournament File...".bright_black().italic(), 1);
-    let parsed_tournament = tournament_parser::parse(tournament_file, &user_input);
-    cmd_step("Finished Parsing File".green(), 2);
+    // Keep the window open long enough to read the result when there's no terminal to return to
+    if launched_by_double_click() && io::stdout().is_terminal() {
+        output::line("Terminal will close in 10s".bright_black().italic());
+        wait(10.0);
+    }
 
-    cmd_step("Connecting To MySql Database...".bright_black().italic(), 3);
-    let (db_conn, db_name) = &mut db::establish_connection();
-    cmd_step(
-        format!("Established MySql Database Connection [{}]", db_name).green(),
-        4,
-    );
+    exit_code
+}
 
-    cmd_step("Inserting Into DB...".bright_black().italic(), 5);
-    db::insert_parsed_tournament(db_conn, parsed_tournament);
-    cmd_step("Finished Inserting Tournament Into Database".green(), 6);
+fn run(cli: &Cli) -> Result<Value, CliError> {
+    let config_path = cli.config.as_deref();
+    let load_config = || -> Result<config::Config, CliError> {
+        let config = config::load(config_path, cli.profile.as_deref())?;
+        commands::print_config(&config);
+        output::line("");
+        Ok(config)
+    };
 
-    println!(
-        "{}{}",
-        "\n[Finished Uploading Tournament To Server]\n"
-            .green()
-            .bold(),
-        format!(
-            "◆ Upload took: {}s\n",
-            (elapsed_start.elapsed().as_millis() as f32 / 1000.0)
-        )
-        .bright_black()
-        .italic()
-    );
-    println!("{}", "Terminal will close in 10s".bright_black().italic());
-
-    // Wait 10 seconds then exit automatically
-    wait(10.0);
+    match &cli.command {
+        Command::Upload(args) => commands::upload(args, &load_config()?),
+        Command::Live(args) => commands::live(args, &load_config()?),
+        Command::Watch {
+            dir,
+            format,
+            interval,
+        } => commands::watch(&load_config()?, dir, format.as_deref(), *interval),
+        Command::Serve { bind } => server::run(&load_config()?, bind),
+        Command::Report {
+            source,
+            out,
+            format,
+        } => commands::report(
+            config_path,
+            cli.profile.as_deref(),
+            source,
+            out,
+            format.as_deref(),
+        ),
+        Command::Bracket {
+            source,
+            out,
+            format,
+        } => commands::bracket(
+            config_path,
+            cli.profile.as_deref(),
+            source,
+            out.as_deref(),
+            format.as_deref(),
+        ),
+        Command::Validate { file, format } => commands::validate(
+            file,
+            &config::load_formats(config_path, cli.profile.as_deref())?.get(format.as_deref())?,
+        ),
+        Command::List { limit } => commands::list(&load_config()?, *limit),
+        Command::Leaderboard {
+            limit,
+            stat,
+            season,
+        } => commands::leaderboard(&load_config()?, *limit, stat.as_deref(), season.as_deref()),
+        Command::Delete { tournament_id, yes } => {
+            commands::delete_tournament(&load_config()?, *tournament_id, *yes)
+        }
+        Command::Recompute { yes } => commands::recompute(&load_config()?, *yes),
+        Command::Promote {
+            tournament_id,
+            from,
+            to,
+        } => commands::promote(config_path, *tournament_id, from, to),
+        Command::Backup { file } => commands::backup(file, &load_config()?),
+        Command::Restore { file } => commands::restore(file, &load_config()?),
+        Command::Player { command } => match command {
+            PlayerCommand::Find { name } => commands::find_player(&load_config()?, name),
+            PlayerCommand::Merge {
+                keep_id,
+                drop_id,
+                yes,
+            } => commands::merge_players(&load_config()?, keep_id, drop_id, *yes),
+        },
+        Command::Season { command } => match command {
+            SeasonCommand::Add { name, start, end } => {
+                commands::add_season(&load_config()?, name, start, end)
+            }
+            SeasonCommand::List => commands::list_seasons(&load_config()?),
+        },
+    }
 }
@@ -1,7 +1,10 @@
 use chrono::NaiveDateTime;
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Varchar};
+use serde::{Deserialize, Serialize};
 
-#[derive(Queryable, Selectable, Debug)]
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
 #[diesel(table_name = crate::schema::Tournament)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct Tournament {
@@ -10,8 +13,15 @@ pub struct Tournament {
     pub date: NaiveDateTime,
     pub title: String,
     pub link: Option<String>,
+    // Archives from before seasons existed don't have this
+    #[serde(default)]
+    pub seasonId: Option<i32>,
+    // Null for tournaments uploaded before formats were stored
+    #[serde(default)]
+    pub format: Option<String>,
 }
 
+#[allow(non_snake_case)]
 #[derive(Insertable, Debug)]
 #[diesel(table_name = crate::schema::Tournament)]
 pub struct NewTournament {
@@ -19,10 +29,57 @@ pub struct NewTournament {
     pub date: NaiveDateTime,
     pub title: String,
     pub link: Option<String>,
+    pub seasonId: Option<i32>,
+    pub format: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::TournamentResult)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct TournamentResult {
+    pub id: i32,
+    pub tournamentId: i32,
+    pub userId: String,
+    /// Empty for players that only got stat points
+    pub placement: Option<i32>,
+    pub points: i32,
+    pub statPoints: i32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate::schema::TournamentResult)]
+pub struct NewTournamentResult {
+    pub tournamentId: i32,
+    pub userId: String,
+    pub placement: Option<i32>,
+    pub points: i32,
+    pub statPoints: i32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::Season)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct Season {
+    pub id: i32,
+    pub name: String,
+    pub startsAt: NaiveDateTime,
+    pub endsAt: NaiveDateTime,
+}
+
+#[allow(non_snake_case)]
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate::schema::Season)]
+pub struct NewSeason {
+    pub name: String,
+    pub startsAt: NaiveDateTime,
+    pub endsAt: NaiveDateTime,
 }
 
 #[allow(non_snake_case)]
-#[derive(Queryable, Selectable, Debug)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
 #[diesel(table_name = crate::schema::Match_)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct Match {
@@ -30,6 +87,17 @@ pub struct Match {
     pub matchType: String,
     pub matchIndex: i32,
     pub tournamentId: Option<i32>,
+    // Archives from before match metadata existed don't have these
+    #[serde(default)]
+    pub map: Option<String>,
+    #[serde(default)]
+    pub mode: Option<String>,
+    #[serde(default)]
+    pub startedAt: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub durationSecs: Option<i32>,
+    #[serde(default)]
+    pub notes: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -40,6 +108,28 @@ pub struct NewMatch {
     pub matchType: String,
     pub matchIndex: i32,
     pub tournamentId: Option<i32>,
+    pub map: Option<String>,
+    pub mode: Option<String>,
+    pub startedAt: Option<NaiveDateTime>,
+    pub durationSecs: Option<i32>,
+    pub notes: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::MatchUser)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct MatchUser {
+    pub id: String,
+    pub username: String,
+    pub userId: String,
+    pub rank: i32,
+    pub lifeStatus: String,
+    pub immuneStatus: String,
+    pub matchId: Option<String>,
+    // Archives from before teams existed don't have this
+    #[serde(default)]
+    pub teamId: Option<String>,
 }
 
 #[allow(non_snake_case)]
@@ -53,10 +143,43 @@ pub struct NewMatchUser {
     pub lifeStatus: String,
     pub immuneStatus: String,
     pub matchId: String,
+    pub teamId: Option<String>,
+}
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::MatchUserStat)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct MatchUserStat {
+    pub id: i32,
+    pub matchUserId: String,
+    pub name: String,
+    pub value: i32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate::schema::MatchUserStat)]
+pub struct NewMatchUserStat {
+    pub matchUserId: String,
+    pub name: String,
+    pub value: i32,
+}
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::MatchTeam)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct MatchTeam {
+    pub id: String,
+    pub name: String,
+    pub rank: i32,
+    pub lifeStatus: String,
+    pub matchId: String,
 }
 
 #[allow(non_snake_case)]
-#[derive(Queryable, Selectable, Debug)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
 #[diesel(table_name = crate::schema::User)]
 #[diesel(check_for_backend(diesel::mysql::Mysql))]
 pub struct User {
@@ -75,3 +198,75 @@ pub struct NewUser {
     pub wins: i32,
     pub ranking: i32,
 }
+
+#[allow(non_snake_case)]
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::UserAlias)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct UserAlias {
+    pub id: i32,
+    pub userId: String,
+    pub username: String,
+    pub changedAt: NaiveDateTime,
+}
+
+#[allow(non_snake_case)]
+#[derive(Insertable, Debug)]
+#[diesel(table_name = crate::schema::UserAlias)]
+pub struct NewUserAlias {
+    pub userId: String,
+    pub username: String,
+    pub changedAt: NaiveDateTime,
+}
+
+#[derive(Queryable, Selectable, Insertable, Serialize, Deserialize, Debug)]
+#[diesel(table_name = crate::schema::NextTournament)]
+#[diesel(check_for_backend(diesel::mysql::Mysql))]
+pub struct NextTournament {
+    pub id: i32,
+    pub date: NaiveDateTime,
+    pub title: String,
+    pub description: String,
+    pub link: Option<String>,
+}
+
+/// Total of one stat over all matches of a user, for `leaderboard --stat`
+#[derive(QueryableByName, Serialize, Debug)]
+pub struct StatTotal {
+    #[diesel(sql_type = Varchar, column_name = userId)]
+    pub user_id: String,
+    #[diesel(sql_type = Varchar)]
+    pub username: String,
+    #[diesel(sql_type = BigInt)]
+    pub total: i64,
+}
+
+/// A user's results over the tournaments of one season, for `leaderboard --season`
+#[derive(QueryableByName, Serialize, Debug)]
+pub struct SeasonStanding {
+    #[diesel(sql_type = Varchar, column_name = userId)]
+    pub user_id: String,
+    #[diesel(sql_type = Varchar)]
+    pub username: String,
+    #[diesel(sql_type = BigInt)]
+    pub points: i64,
+    #[diesel(sql_type = BigInt)]
+    pub wins: i64,
+    #[diesel(sql_type = BigInt)]
+    pub tournaments: i64,
+}
+
+/// A row of prisma's implicit many-to-many table `_TournamentToUser`
+#[derive(QueryableByName, Debug)]
+pub struct Count {
+    #[diesel(sql_type = BigInt)]
+    pub count: i64,
+}
+
+#[derive(QueryableByName, Serialize, Deserialize, Debug)]
+pub struct TournamentToUser {
+    #[diesel(sql_type = Integer, column_name = A)]
+    pub tournament_id: i32,
+    #[diesel(sql_type = Varchar, column_name = B)]
+    pub user_id: String,
+}
@@ -0,0 +1,128 @@
+use std::{fmt::Display, sync::OnceLock};
+
+use clap::ValueEnum;
+use colored::{ColoredString, Colorize};
+use serde_json::{json, Value};
+
+use crate::error::CliError;
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Default)]
+pub enum OutputMode {
+    /// Coloured text for people
+    #[default]
+    Human,
+    /// One JSON object per line for scripts & bots
+    Json,
+}
+
+static MODE: OnceLock<OutputMode> = OnceLock::new();
+
+pub fn set_mode(mode: OutputMode) {
+    let _ = MODE.set(mode);
+}
+
+pub fn is_json() -> bool {
+    MODE.get().copied().unwrap_or_default() == OutputMode::Json
+}
+
+/// Prints a line in human mode only, for banners, tables & other decoration
+pub fn line(msg: impl Display) {
+    if !is_json() {
+        println!("{}", msg);
+    }
+}
+
+pub fn step(msg: ColoredString, step: i32, steps: i32) {
+    if is_json() {
+        emit(json!({
+            "type": "step",
+            "step": step,
+            "steps": steps,
+            // Deref gives the text without any colour codes
+            "message": &*msg,
+        }));
+        return;
+    }
+
+    println!(
+        "{}{}{}{}",
+        "[".bright_black(),
+        format!("{step}/{steps}").white(),
+        "] ".bright_black(),
+        msg
+    );
+}
+
+pub fn warning(title: &str, details: &str) {
+    if is_json() {
+        emit(json!({
+            "type": "warning",
+            "message": format!("{}{}", title, details),
+        }));
+        return;
+    }
+
+    println!("{}{}", title.yellow(), details.bright_black().italic());
+}
+
+pub fn error(error: &CliError) {
+    if is_json() {
+        eprintln!(
+            "{}",
+            json!({
+                "type": "error",
+                "kind": error.to_str(),
+                "exit_code": error.exit_code(),
+                "message": error.message(),
+            })
+        );
+        return;
+    }
+
+    eprintln!(
+        "{}{}",
+        format!("\n[{}] ", error.to_str()).red().bold(),
+        error.message().red()
+    );
+}
+
+/// Prints a JSON object with `type` set to `event_type` & the fields of `data`, JSON mode only
+pub fn event(event_type: &str, data: Value) {
+    if !is_json() {
+        return;
+    }
+
+    let mut event = json!({ "type": event_type });
+    if let (Some(event), Value::Object(data)) = (event.as_object_mut(), data) {
+        event.extend(data);
+    }
+
+    emit(event);
+}
+
+/// The final result of a command, `data` is merged into the printed object
+pub fn result(command: &str, data: Value, elapsed_secs: f32) {
+    if is_json() {
+        let mut result = json!({
+            "command": command,
+            "elapsed_secs": elapsed_secs,
+        });
+        if let (Some(result), Value::Object(data)) = (result.as_object_mut(), data) {
+            result.extend(data);
+        }
+
+        event("result", result);
+        return;
+    }
+
+    println!(
+        "{}",
+        format!("◆ Took: {}s\n", elapsed_secs)
+            .bright_black()
+            .italic()
+    );
+}
+
+fn emit(value: Value) {
+    println!("{}", value);
+}
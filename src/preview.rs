@@ -0,0 +1,235 @@
+use colored::Colorize;
+use diesel::mysql::MysqlConnection;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::bracket::{self, Violation};
+use crate::db;
+use crate::error::CliError;
+use crate::output;
+use crate::tournament_parser::{self, ParsedTournament};
+
+/// What an upload is about to change, shown before anything is written
+#[derive(Serialize, Debug)]
+pub struct Preview {
+    pub title: String,
+    pub date: String,
+    /// Amount of matches & players per match type, in file order
+    pub matches: Vec<MatchPreview>,
+    pub unique_players: usize,
+    pub winner: Option<String>,
+    pub top: Vec<Placement>,
+    pub new_players: Vec<String>,
+    pub renames: Vec<Rename>,
+    /// Broken bracket progression, shown as a warning
+    pub violations: Vec<Violation>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct MatchPreview {
+    pub match_type: String,
+    pub matches: usize,
+    pub teams: usize,
+    pub players: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Placement {
+    pub placement: usize,
+    pub username: String,
+    pub user_id: String,
+    /// Set in team tournaments, where every member is listed with the team's placement
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Rename {
+    pub user_id: String,
+    pub from: String,
+    pub to: String,
+}
+
+pub fn build(
+    conn: &mut MysqlConnection,
+    tournament: &ParsedTournament,
+    top_count: usize,
+) -> Result<Preview, CliError> {
+    let mut matches: Vec<MatchPreview> = vec![];
+    for m in &tournament.matches {
+        let match_type = &m.match_type.key;
+        match matches.iter_mut().find(|p| &p.match_type == match_type) {
+            Some(preview) => {
+                preview.matches += 1;
+                preview.teams += m.teams.len();
+                preview.players += m.players.len();
+            }
+            None => matches.push(MatchPreview {
+                match_type: match_type.to_owned(),
+                matches: 1,
+                teams: m.teams.len(),
+                players: m.players.len(),
+            }),
+        }
+    }
+
+    let placements = tournament_parser::get_placements(tournament);
+    let top: Vec<Placement> = placements
+        .iter()
+        .take(top_count)
+        .enumerate()
+        .flat_map(|(i, users)| {
+            users.iter().map(move |user| Placement {
+                placement: i + 1,
+                username: user.username.to_owned(),
+                user_id: user.user_id.to_owned(),
+                team: user.team.to_owned(),
+            })
+        })
+        .collect();
+    let winner = placements
+        .first()
+        .and_then(|users| users.first())
+        .map(|user| {
+            user.team
+                .to_owned()
+                .unwrap_or_else(|| user.username.to_owned())
+        });
+
+    let players = tournament_parser::get_unique_players(&tournament.matches);
+    let user_ids: Vec<String> = players.iter().map(|p| p.user_id.to_owned()).collect();
+    let existing = db::get_users(conn, &user_ids)?;
+    let latest = db::latest_tournament_dates(conn, &user_ids, None)?;
+
+    let mut new_players: Vec<String> = vec![];
+    let mut renames: Vec<Rename> = vec![];
+    for player in &players {
+        match existing.iter().find(|u| u.userId == player.user_id) {
+            Some(user) if db::renames_user(user, &player.username, &latest, tournament.date) => {
+                renames.push(Rename {
+                    user_id: player.user_id.to_owned(),
+                    from: user.username.to_owned(),
+                    to: player.username.to_owned(),
+                })
+            }
+            Some(_) => {}
+            None => new_players.push(player.username.to_owned()),
+        }
+    }
+
+    Ok(Preview {
+        title: tournament.title.to_owned(),
+        date: tournament.date.format("%Y-%m-%d %H:%M").to_string(),
+        matches,
+        unique_players: players.len(),
+        winner,
+        top,
+        new_players,
+        renames,
+        violations: bracket::check(tournament),
+    })
+}
+
+pub fn print(preview: &Preview) {
+    if output::is_json() {
+        output::event("preview", json!(preview));
+        return;
+    }
+
+    output::line(format!(
+        "\n{}{}",
+        format!("[{}] ", preview.title).blue().bold(),
+        preview.date.bright_black()
+    ));
+
+    for m in &preview.matches {
+        output::line(format!(
+            "{}{}",
+            "◆ ".green(),
+            format!(
+                "{}: {} Match{}, {}{} Players",
+                m.match_type,
+                m.matches,
+                if m.matches == 1 { "" } else { "es" },
+                match m.teams {
+                    0 => String::new(),
+                    teams => format!("{} Teams, ", teams),
+                },
+                m.players
+            )
+            .white()
+        ));
+    }
+    output::line(format!(
+        "{}{}",
+        "◆ ".green(),
+        format!("{} Unique Players", preview.unique_players).white()
+    ));
+    output::line(format!(
+        "{}{}",
+        "◆ Winner: ".green(),
+        preview.winner.as_deref().unwrap_or("None").white().bold()
+    ));
+
+    output::line("\nTop Placements".white().bold());
+    for (i, placement) in preview.top.iter().enumerate() {
+        let previous = i.checked_sub(1).map(|i| &preview.top[i]);
+        match &placement.team {
+            // Team members share a placement, print it once with all of them
+            Some(team) if previous.map(|p| p.placement) != Some(placement.placement) => {
+                let members: Vec<&str> = preview
+                    .top
+                    .iter()
+                    .filter(|p| p.placement == placement.placement)
+                    .map(|p| p.username.as_str())
+                    .collect();
+                output::line(format!(
+                    "{}{}{}",
+                    format!("{:>3}. ", placement.placement).bright_black(),
+                    team.white(),
+                    format!(" ({})", members.join(", ")).bright_black()
+                ));
+            }
+            Some(_) => {}
+            None => output::line(format!(
+                "{}{}",
+                format!("{:>3}. ", placement.placement).bright_black(),
+                placement.username.white()
+            )),
+        }
+    }
+
+    output::line(
+        format!("\nNew Players ({})", preview.new_players.len())
+            .white()
+            .bold(),
+    );
+    for username in &preview.new_players {
+        output::line(format!("{}{}", "  + ".green(), username));
+    }
+
+    output::line(
+        format!("\nUsername Changes ({})", preview.renames.len())
+            .white()
+            .bold(),
+    );
+    for rename in &preview.renames {
+        output::line(format!(
+            "{}{}{}{}",
+            "  ~ ".yellow(),
+            rename.from.bright_black(),
+            " -> ".yellow(),
+            rename.to
+        ));
+    }
+
+    if !preview.violations.is_empty() {
+        output::line(
+            format!("\nBracket Issues ({})", preview.violations.len())
+                .yellow()
+                .bold(),
+        );
+        bracket::print(&preview.violations);
+    }
+    output::line("");
+}
@@ -0,0 +1,324 @@
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use serde::Serialize;
+
+use crate::config::ScoringSystem;
+use crate::tournament_parser::{
+    self, ImmuneStatus, LifeStatus, Match, MatchUser, ParsedTournament,
+};
+
+const STYLE: &str =
+    "body{font-family:sans-serif;max-width:960px;margin:2em auto;padding:0 1em;color:#222}\
+table{border-collapse:collapse;width:100%;margin-bottom:1.5em}\
+th,td{border:1px solid #ccc;padding:.3em .6em;text-align:left}\
+th{background:#f2f2f2}\
+.eliminated{color:#999}.playing{color:#b58900}\
+.immune{color:#268bd2;font-weight:bold}.saved{color:#2aa198;font-weight:bold}\
+.muted{color:#777}";
+
+/// One placement of the overall standings with the ranking points it's worth
+#[derive(Serialize, Debug)]
+pub struct Standing {
+    pub placement: usize,
+    /// One player, or every member of a team
+    pub players: Vec<String>,
+    pub team: Option<String>,
+    pub points: i32,
+    /// From the profile's `stat_points`, summed over the players
+    pub stat_points: i32,
+}
+
+pub fn standings(
+    tournament: &ParsedTournament,
+    scoring: &ScoringSystem,
+    stat_points: &HashMap<String, i32>,
+) -> Vec<Standing> {
+    let user_stat_points = tournament.stat_points(stat_points);
+    let placements = tournament_parser::get_placements(tournament);
+
+    placements
+        .iter()
+        .enumerate()
+        .map(|(i, users)| Standing {
+            placement: i + 1,
+            players: users.iter().map(|user| user.username.to_owned()).collect(),
+            team: users.first().and_then(|user| user.team.to_owned()),
+            points: scoring.points(i, placements.len()),
+            stat_points: users
+                .iter()
+                .filter_map(|user| {
+                    user_stat_points
+                        .iter()
+                        .find(|(u, _)| u.user_id == user.user_id)
+                        .map(|(_, points)| points)
+                })
+                .sum(),
+        })
+        .collect()
+}
+
+/// File name without extension for a tournament title, e.g. `friday-night-bots`
+pub fn file_stem(title: &str) -> String {
+    let stem = title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<&str>>()
+        .join("-");
+
+    match stem.is_empty() {
+        true => String::from("tournament"),
+        false => stem,
+    }
+}
+
+pub fn markdown(tournament: &ParsedTournament, standings: &[Standing]) -> String {
+    let mut md = String::new();
+    let _ = writeln!(md, "# {}\n", tournament.title);
+    let _ = write!(md, "{}", tournament.date.format("%Y-%m-%d %H:%M"));
+    if let Some(link) = &tournament.link {
+        let _ = write!(md, " · [Video]({})", link);
+    }
+    md.push_str("\n\n## Standings\n\n");
+
+    let with_stat_points = standings.iter().any(|s| s.stat_points != 0);
+    md.push_str("| # | Player | Points |");
+    md.push_str(if with_stat_points {
+        " Stat Points |\n"
+    } else {
+        "\n"
+    });
+    md.push_str("| - | ------ | ------ |");
+    md.push_str(if with_stat_points {
+        " ----------- |\n"
+    } else {
+        "\n"
+    });
+    for standing in standings {
+        let _ = write!(
+            md,
+            "| {} | {} | {} |",
+            standing.placement,
+            md_escape(&standing_name(standing)),
+            standing.points
+        );
+        match with_stat_points {
+            true => {
+                let _ = writeln!(md, " {} |", standing.stat_points);
+            }
+            false => md.push('\n'),
+        }
+    }
+
+    for m in in_round_order(tournament) {
+        let _ = write!(md, "\n## {}", m.match_type.name);
+        if let Some(map) = &m.info.map {
+            let _ = write!(md, " · {}", md_escape(map));
+        }
+        md.push_str("\n\n");
+        if let Some(notes) = &m.info.notes {
+            let _ = writeln!(md, "_{}_\n", md_escape(notes).replace('\n', " "));
+        }
+
+        let stats = stat_names(m);
+        let mut columns: Vec<String> = vec![String::from("Rank"), String::from("Player")];
+        if !m.teams.is_empty() {
+            columns.push(String::from("Team"));
+        }
+        columns.push(String::from("Status"));
+        columns.extend(stats.iter().map(|stat| stat.to_owned()));
+        let _ = writeln!(md, "| {} |", columns.join(" | "));
+        let _ = writeln!(md, "|{}", " --- |".repeat(columns.len()));
+
+        for player in &m.players {
+            let mut cells: Vec<String> = vec![player.rank.to_string(), md_escape(&player.username)];
+            if !m.teams.is_empty() {
+                cells.push(md_escape(player.team.as_deref().unwrap_or("")));
+            }
+            cells.push(status(player));
+            cells.extend(stats.iter().map(|stat| stat_value(player, stat)));
+            let _ = writeln!(md, "| {} |", cells.join(" | "));
+        }
+    }
+
+    md
+}
+
+/// A single page with inline styling and no external resources
+pub fn html(tournament: &ParsedTournament, standings: &[Standing]) -> String {
+    let mut page = String::new();
+    let title = escape(&tournament.title);
+    let _ = write!(
+        page,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<h1>{}</h1>\n<p class=\"muted\">{}",
+        title,
+        STYLE,
+        title,
+        tournament.date.format("%Y-%m-%d %H:%M")
+    );
+    if let Some(link) = &tournament.link {
+        let _ = write!(page, " · <a href=\"{}\">Video</a>", escape(link));
+    }
+    page.push_str(
+        "</p>\n<h2>Standings</h2>\n<table>\n<tr><th>#</th><th>Player</th><th>Points</th>",
+    );
+
+    let with_stat_points = standings.iter().any(|s| s.stat_points != 0);
+    if with_stat_points {
+        page.push_str("<th>Stat Points</th>");
+    }
+    page.push_str("</tr>\n");
+    for standing in standings {
+        let _ = write!(
+            page,
+            "<tr><td>{}</td><td>{}</td><td>{}</td>",
+            standing.placement,
+            escape(&standing_name(standing)),
+            standing.points
+        );
+        if with_stat_points {
+            let _ = write!(page, "<td>{}</td>", standing.stat_points);
+        }
+        page.push_str("</tr>\n");
+    }
+    page.push_str("</table>\n");
+
+    for m in in_round_order(tournament) {
+        let _ = write!(page, "<h2>{}", escape(&m.match_type.name));
+        if let Some(map) = &m.info.map {
+            let _ = write!(page, " <span class=\"muted\">· {}</span>", escape(map));
+        }
+        page.push_str("</h2>\n");
+        if let Some(notes) = &m.info.notes {
+            let _ = writeln!(
+                page,
+                "<p class=\"muted\">{}</p>",
+                escape(notes).replace('\n', "<br>")
+            );
+        }
+
+        let stats = stat_names(m);
+        page.push_str("<table>\n<tr><th>Rank</th><th>Player</th>");
+        if !m.teams.is_empty() {
+            page.push_str("<th>Team</th>");
+        }
+        page.push_str("<th>Status</th>");
+        for stat in &stats {
+            let _ = write!(page, "<th>{}</th>", escape(stat));
+        }
+        page.push_str("</tr>\n");
+
+        for player in &m.players {
+            let _ = write!(
+                page,
+                "<tr class=\"{}\"><td>{}</td><td>{}</td>",
+                player.life_status.to_str(),
+                player.rank,
+                escape(&player.username)
+            );
+            if !m.teams.is_empty() {
+                let _ = write!(
+                    page,
+                    "<td>{}</td>",
+                    escape(player.team.as_deref().unwrap_or(""))
+                );
+            }
+            let _ = write!(page, "<td>{}</td>", status_html(player));
+            for stat in &stats {
+                let _ = write!(page, "<td>{}</td>", stat_value(player, stat));
+            }
+            page.push_str("</tr>\n");
+        }
+        page.push_str("</table>\n");
+    }
+
+    page.push_str(&format!(
+        "<p class=\"muted\">Generated by pbt-cli {}</p>\n</body>\n</html>\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+
+    page
+}
+
+/// Game 1 first, the final last, files may list the rounds in any order
+fn in_round_order(tournament: &ParsedTournament) -> Vec<&Match> {
+    let mut matches: Vec<&Match> = tournament.matches.iter().collect();
+    matches.sort_by_key(|m| (m.match_type.order, m.match_index));
+    matches
+}
+
+fn standing_name(standing: &Standing) -> String {
+    match &standing.team {
+        Some(team) => format!("{} ({})", team, standing.players.join(", ")),
+        None => standing.players.join(", "),
+    }
+}
+
+fn life_status(player: &MatchUser) -> &'static str {
+    match player.life_status {
+        LifeStatus::Alive => "Alive",
+        LifeStatus::Eliminated => "Eliminated",
+        LifeStatus::Playing => "Playing",
+    }
+}
+
+fn immune_status(player: &MatchUser) -> Option<&'static str> {
+    match player.immune_status {
+        ImmuneStatus::Immune => Some("Immune"),
+        ImmuneStatus::Saved => Some("Saved"),
+        ImmuneStatus::None => None,
+    }
+}
+
+fn status(player: &MatchUser) -> String {
+    match immune_status(player) {
+        Some(immunity) => format!("{}, **{}**", life_status(player), immunity),
+        None => life_status(player).to_owned(),
+    }
+}
+
+fn status_html(player: &MatchUser) -> String {
+    match immune_status(player) {
+        Some(immunity) => format!(
+            "{} <span class=\"{}\">{}</span>",
+            life_status(player),
+            immunity.to_lowercase(),
+            immunity
+        ),
+        None => life_status(player).to_owned(),
+    }
+}
+
+/// Stats given for any player of the match, sorted
+fn stat_names(m: &Match) -> Vec<String> {
+    let mut names: Vec<String> = m
+        .players
+        .iter()
+        .flat_map(|player| player.stats.keys().cloned())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+fn stat_value(player: &MatchUser, stat: &str) -> String {
+    player
+        .stats
+        .get(stat)
+        .map(|value| value.to_string())
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn md_escape(text: &str) -> String {
+    text.replace('|', "\\|")
+        .replace('*', "\\*")
+        .replace('_', "\\_")
+}
@@ -0,0 +1,235 @@
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::json;
+
+use crate::input;
+use crate::output;
+use crate::tournament_parser::ParsedTournament;
+
+/// Lowest similarity (0..1) for a username to be offered as a match
+const MIN_SIMILARITY: f64 = 0.6;
+/// Most candidates shown when picking a match
+const MAX_CANDIDATES: usize = 5;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct Candidate {
+    pub user_id: String,
+    pub username: String,
+    pub similarity: f64,
+}
+
+/// A player line without an id that got one from a known username
+#[derive(Serialize, Debug)]
+pub struct Resolved {
+    pub username: String,
+    pub user_id: String,
+    pub matched: String,
+}
+
+/// A player line without an id that couldn't be resolved
+#[derive(Serialize, Debug)]
+pub struct Unresolved {
+    pub username: String,
+    /// The id as written when there was one that isn't a valid Steam id
+    pub invalid_id: Option<String>,
+    pub candidates: Vec<Candidate>,
+}
+
+/// Usernames of player lines with an empty or invalid id, in file order
+pub fn missing_usernames(tournament: &ParsedTournament) -> Vec<String> {
+    let mut usernames: Vec<String> = vec![];
+    for player in tournament.matches.iter().flat_map(|m| &m.players) {
+        if player.user_id.is_empty() && !usernames.contains(&player.username) {
+            usernames.push(player.username.to_owned());
+        }
+    }
+
+    usernames
+}
+
+/// `(user_id, username)` of every player line in the file that does have an id
+pub fn file_usernames(tournament: &ParsedTournament) -> Vec<(String, String)> {
+    tournament
+        .matches
+        .iter()
+        .flat_map(|m| &m.players)
+        .filter(|player| !player.user_id.is_empty())
+        .map(|player| (player.user_id.to_owned(), player.username.to_owned()))
+        .collect()
+}
+
+/// Fills in empty ids by matching the username against `known` `(user_id, username)` pairs.
+/// A single exact (case-insensitive) match is used directly, anything else is asked about
+/// when `interactive`, otherwise it's left unresolved
+pub fn resolve_missing_ids(
+    tournament: &mut ParsedTournament,
+    known: &[(String, String)],
+    interactive: bool,
+) -> (Vec<Resolved>, Vec<Unresolved>) {
+    let mut resolved: Vec<Resolved> = vec![];
+    let mut unresolved: Vec<Unresolved> = vec![];
+
+    for username in missing_usernames(tournament) {
+        let invalid_id = invalid_id(tournament, &username);
+        let candidates = candidates(&username, known);
+        let exact: Vec<&Candidate> = candidates.iter().filter(|c| c.similarity == 1.0).collect();
+
+        let picked = match exact.as_slice() {
+            [candidate] => Some((*candidate).clone()),
+            _ if candidates.is_empty() || !interactive => None,
+            _ => {
+                let options: Vec<String> = candidates
+                    .iter()
+                    .map(|c| {
+                        format!(
+                            "{} [{}] {:.0}%",
+                            c.username,
+                            c.user_id,
+                            c.similarity * 100.0
+                        )
+                    })
+                    .collect();
+                let question = match &invalid_id {
+                    Some(id) => format!("Invalid Steam id '{}' for \"{}\", is it:", id, username),
+                    None => format!("No Steam id for \"{}\", is it:", username),
+                };
+                input::choose(&question, &options).map(|i| candidates[i].clone())
+            }
+        };
+
+        match picked {
+            Some(candidate) => {
+                set_user_id(tournament, &username, &candidate.user_id);
+                resolved.push(Resolved {
+                    username,
+                    user_id: candidate.user_id,
+                    matched: candidate.username,
+                });
+            }
+            None => unresolved.push(Unresolved {
+                username,
+                invalid_id,
+                candidates,
+            }),
+        }
+    }
+
+    (resolved, unresolved)
+}
+
+pub fn print_resolved(resolved: &[Resolved]) {
+    for r in resolved {
+        if output::is_json() {
+            output::event("resolved", json!(r));
+            continue;
+        }
+
+        output::line(format!(
+            "{}{}{}",
+            "◆ Resolved ".green(),
+            r.username.white(),
+            format!(" -> {} [{}]", r.matched, r.user_id).bright_black()
+        ));
+    }
+}
+
+/// One line per unresolved player with its closest candidates
+pub fn describe_unresolved(unresolved: &[Unresolved]) -> String {
+    unresolved
+        .iter()
+        .map(|u| match u.candidates.is_empty() {
+            true => format!(
+                "\"{}\" {} and no similar known username",
+                u.username,
+                describe_id(u)
+            ),
+            false => format!(
+                "\"{}\" {}, did you mean {}?",
+                u.username,
+                describe_id(u),
+                u.candidates
+                    .iter()
+                    .map(|c| format!("{} [{}]", c.username, c.user_id))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+fn describe_id(unresolved: &Unresolved) -> String {
+    match &unresolved.invalid_id {
+        Some(id) => format!("has an invalid id ({})", id),
+        None => String::from("has no id"),
+    }
+}
+
+/// Best match per user id, most similar first
+fn candidates(username: &str, known: &[(String, String)]) -> Vec<Candidate> {
+    let mut candidates: Vec<Candidate> = vec![];
+    for (user_id, known_name) in known {
+        let similarity = similarity(username, known_name);
+        if similarity < MIN_SIMILARITY {
+            continue;
+        }
+
+        match candidates.iter_mut().find(|c| &c.user_id == user_id) {
+            Some(candidate) if candidate.similarity < similarity => {
+                candidate.username = known_name.to_owned();
+                candidate.similarity = similarity;
+            }
+            Some(_) => {}
+            None => candidates.push(Candidate {
+                user_id: user_id.to_owned(),
+                username: known_name.to_owned(),
+                similarity,
+            }),
+        }
+    }
+
+    candidates.sort_by(|a, b| b.similarity.total_cmp(&a.similarity));
+    candidates.truncate(MAX_CANDIDATES);
+    candidates
+}
+
+/// Levenshtein distance scaled to 0..1 over the longer name, ignoring case
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.trim().to_lowercase().chars().collect();
+    let b: Vec<char> = b.trim().to_lowercase().chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 0.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
+
+fn set_user_id(tournament: &mut ParsedTournament, username: &str, user_id: &str) {
+    for player in tournament.matches.iter_mut().flat_map(|m| &mut m.players) {
+        if player.user_id.is_empty() && player.username == username {
+            player.user_id = user_id.to_owned();
+            player.invalid_id = None;
+        }
+    }
+}
+
+/// The invalid id written for a username, if any of its lines had one
+fn invalid_id(tournament: &ParsedTournament, username: &str) -> Option<String> {
+    tournament
+        .matches
+        .iter()
+        .flat_map(|m| &m.players)
+        .filter(|player| player.user_id.is_empty() && player.username == username)
+        .find_map(|player| player.invalid_id.to_owned())
+}
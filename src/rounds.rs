@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CliError;
+
+pub const DEFAULT_FORMAT: &str = "default";
+
+/// One round of a tournament format, matched against the `#<name>` headers of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Round {
+    /// Header in the tournament file, without the `#`
+    pub name: String,
+    /// Stored as `Match.matchType`
+    pub key: String,
+    /// Position in the tournament, the first round has the lowest order
+    pub order: i32,
+    /// Whether eliminated players are out of the tournament, false for side rounds like Bonus
+    #[serde(default = "default_elimination")]
+    pub elimination: bool,
+    /// Whether the round's rank 1 may skip ahead to any later round instead of the next one
+    #[serde(default)]
+    pub winner_bye: bool,
+}
+
+fn default_elimination() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundFormat {
+    /// Key of the format in the config, stored with each tournament to read it back
+    #[serde(skip)]
+    pub name: String,
+    pub rounds: Vec<Round>,
+}
+
+impl Default for RoundFormat {
+    /// The rounds every tournament used before formats were configurable
+    fn default() -> Self {
+        // The Quarterfinal winner goes straight to the Final
+        let rounds = [
+            ("Game 1", "game_1", true, false),
+            ("Game 2", "game_2", true, false),
+            ("Game 3", "game_3", true, false),
+            ("Game 4", "game_4", true, false),
+            ("Bonus", "bonus", false, false),
+            ("Quarterfinal", "quarterfinal", true, true),
+            ("Semifinal", "semifinal", true, false),
+            ("Final", "final", true, false),
+        ];
+
+        RoundFormat {
+            name: DEFAULT_FORMAT.to_owned(),
+            rounds: rounds
+                .into_iter()
+                .enumerate()
+                .map(|(i, (name, key, elimination, winner_bye))| Round {
+                    name: name.to_owned(),
+                    key: key.to_owned(),
+                    order: i as i32 + 1,
+                    elimination,
+                    winner_bye,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RoundFormat {
+    /// The round for a `#<name>` header line, names are matched ignoring case
+    pub fn by_header(&self, header: &str) -> Option<&Round> {
+        let name = header.trim_start_matches('#').trim();
+        self.rounds
+            .iter()
+            .find(|round| round.name.eq_ignore_ascii_case(name))
+    }
+
+    pub fn by_key(&self, key: &str) -> Option<&Round> {
+        self.rounds.iter().find(|round| round.key == key)
+    }
+
+    /// The round with the highest order, its alive players are placed first
+    pub fn final_round(&self) -> Option<&Round> {
+        self.rounds.iter().max_by_key(|round| round.order)
+    }
+
+    /// Round names in order, for error messages
+    pub fn names(&self) -> String {
+        let mut rounds: Vec<&Round> = self.rounds.iter().collect();
+        rounds.sort_by_key(|round| round.order);
+        rounds
+            .iter()
+            .map(|round| round.name.as_str())
+            .collect::<Vec<&str>>()
+            .join(", ")
+    }
+
+    fn check(&self, format_name: &str) -> Result<(), String> {
+        if self.rounds.is_empty() {
+            return Err(format!("Format '{}' has no rounds", format_name));
+        }
+
+        for (i, round) in self.rounds.iter().enumerate() {
+            for other in &self.rounds[i + 1..] {
+                let duplicate = if round.name.eq_ignore_ascii_case(&other.name) {
+                    Some("name")
+                } else if round.key == other.key {
+                    Some("key")
+                } else if round.order == other.order {
+                    Some("order")
+                } else {
+                    None
+                };
+                if let Some(field) = duplicate {
+                    return Err(format!(
+                        "Format '{}' has rounds '{}' & '{}' with the same {}",
+                        format_name, round.name, other.name, field
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The formats from the config, with the one the profile uses unless `--format` is given
+#[derive(Debug, Clone, Default)]
+pub struct Formats {
+    pub profile_format: Option<String>,
+    pub formats: HashMap<String, RoundFormat>,
+}
+
+impl Formats {
+    /// `name`, else the profile's format, else `default` which is built in unless the config
+    /// overrides it
+    pub fn get(&self, name: Option<&str>) -> Result<RoundFormat, CliError> {
+        let name = name
+            .or(self.profile_format.as_deref())
+            .unwrap_or(DEFAULT_FORMAT);
+
+        let format = match self.formats.get(name) {
+            Some(format) => RoundFormat {
+                name: name.to_owned(),
+                ..format.to_owned()
+            },
+            None if name == DEFAULT_FORMAT => RoundFormat::default(),
+            None => {
+                let mut names: Vec<&str> = self.formats.keys().map(|n| n.as_str()).collect();
+                names.push(DEFAULT_FORMAT);
+                names.sort();
+                names.dedup();
+                return Err(CliError::Config(format!(
+                    "No format named '{}', available: {}",
+                    name,
+                    names.join(", ")
+                )));
+            }
+        };
+        format.check(name).map_err(CliError::Config)?;
+
+        Ok(format)
+    }
+}
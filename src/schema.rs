@@ -10,6 +10,28 @@ diesel::table! {
         matchType -> Varchar,
         matchIndex -> Integer,
         tournamentId -> Nullable<Integer>,
+        #[max_length = 191]
+        map -> Nullable<Varchar>,
+        #[max_length = 191]
+        mode -> Nullable<Varchar>,
+        startedAt -> Nullable<Datetime>,
+        durationSecs -> Nullable<Integer>,
+        notes -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    #[allow(non_snake_case)]
+    MatchTeam (id) {
+        #[max_length = 191]
+        id -> Varchar,
+        #[max_length = 191]
+        name -> Varchar,
+        rank -> Integer,
+        #[max_length = 191]
+        lifeStatus -> Varchar,
+        #[max_length = 191]
+        matchId -> Varchar,
     }
 }
 
@@ -29,6 +51,20 @@ diesel::table! {
         immuneStatus -> Varchar,
         #[max_length = 191]
         matchId -> Nullable<Varchar>,
+        #[max_length = 191]
+        teamId -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    #[allow(non_snake_case)]
+    MatchUserStat (id) {
+        id -> Integer,
+        #[max_length = 191]
+        matchUserId -> Varchar,
+        #[max_length = 191]
+        name -> Varchar,
+        value -> Integer,
     }
 }
 
@@ -46,6 +82,17 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    #[allow(non_snake_case)]
+    Season (id) {
+        id -> Integer,
+        #[max_length = 191]
+        name -> Varchar,
+        startsAt -> Datetime,
+        endsAt -> Datetime,
+    }
+}
+
 diesel::table! {
     #[allow(non_snake_case)]
     Tournament (id) {
@@ -56,6 +103,22 @@ diesel::table! {
         title -> Varchar,
         #[max_length = 191]
         link -> Nullable<Varchar>,
+        seasonId -> Nullable<Integer>,
+        #[max_length = 191]
+        format -> Nullable<Varchar>,
+    }
+}
+
+diesel::table! {
+    #[allow(non_snake_case)]
+    TournamentResult (id) {
+        id -> Integer,
+        tournamentId -> Integer,
+        #[max_length = 191]
+        userId -> Varchar,
+        placement -> Nullable<Integer>,
+        points -> Integer,
+        statPoints -> Integer,
     }
 }
 
@@ -71,8 +134,38 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    #[allow(non_snake_case)]
+    UserAlias (id) {
+        id -> Integer,
+        #[max_length = 191]
+        userId -> Varchar,
+        #[max_length = 191]
+        username -> Varchar,
+        changedAt -> Datetime,
+    }
+}
+
 diesel::joinable!(Match_ -> Tournament (tournamentId));
+diesel::joinable!(MatchTeam -> Match_ (matchId));
 diesel::joinable!(MatchUser -> Match_ (matchId));
+diesel::joinable!(MatchUser -> MatchTeam (teamId));
 diesel::joinable!(MatchUser -> User (userId));
+diesel::joinable!(MatchUserStat -> MatchUser (matchUserId));
+diesel::joinable!(Tournament -> Season (seasonId));
+diesel::joinable!(TournamentResult -> Tournament (tournamentId));
+diesel::joinable!(TournamentResult -> User (userId));
+diesel::joinable!(UserAlias -> User (userId));
 
-diesel::allow_tables_to_appear_in_same_query!(Match_, MatchUser, NextTournament, Tournament, User,);
+diesel::allow_tables_to_appear_in_same_query!(
+    Match_,
+    MatchTeam,
+    MatchUser,
+    MatchUserStat,
+    NextTournament,
+    Season,
+    Tournament,
+    TournamentResult,
+    User,
+    UserAlias,
+);
@@ -0,0 +1,61 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use diesel::mysql::MysqlConnection;
+
+use crate::db;
+use crate::error::CliError;
+use crate::models::{NewSeason, Season};
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// A season from the first day at 00:00 through the last day at 23:59:59
+pub fn new_season(name: &str, start: &str, end: &str) -> Result<NewSeason, CliError> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(CliError::Usage(String::from("A season needs a name")));
+    }
+
+    let starts_at = parse_day(start)?.and_hms_opt(0, 0, 0);
+    let ends_at = parse_day(end)?.and_hms_opt(23, 59, 59);
+    let (Some(starts_at), Some(ends_at)) = (starts_at, ends_at) else {
+        return Err(CliError::Usage(String::from("Invalid season dates")));
+    };
+    if ends_at < starts_at {
+        return Err(CliError::Usage(format!(
+            "Season ends ({}) before it starts ({})",
+            end, start
+        )));
+    }
+
+    Ok(NewSeason {
+        name: name.to_owned(),
+        startsAt: starts_at,
+        endsAt: ends_at,
+    })
+}
+
+fn parse_day(date: &str) -> Result<NaiveDate, CliError> {
+    NaiveDate::parse_from_str(date, DATE_FORMAT).map_err(|error| {
+        CliError::Usage(format!(
+            "Failed to parse date '{}' ({:?}), expected YYYY-MM-DD",
+            date,
+            error.kind()
+        ))
+    })
+}
+
+pub fn find(conn: &mut MysqlConnection, name: &str) -> Result<Season, CliError> {
+    db::get_season(conn, name)?.ok_or_else(|| {
+        CliError::Usage(format!(
+            "No season named '{}', create it with `pbt-cli season add`",
+            name
+        ))
+    })
+}
+
+pub fn format_range(starts_at: NaiveDateTime, ends_at: NaiveDateTime) -> String {
+    format!(
+        "{} to {}",
+        starts_at.format(DATE_FORMAT),
+        ends_at.format(DATE_FORMAT)
+    )
+}
@@ -0,0 +1,331 @@
+use std::{collections::HashMap, io::Read};
+
+use colored::Colorize;
+use diesel::mysql::MysqlConnection;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+use crate::audit::{self, AuditEntry};
+use crate::config::Config;
+use crate::db;
+use crate::error::CliError;
+use crate::input;
+use crate::output;
+use crate::preview;
+use crate::resolve;
+use crate::season;
+use crate::tournament_parser::{self, ParsedTournament};
+use crate::webhook;
+
+// Tournament files are a few hundred KB at most
+const MAX_BODY_BYTES: u64 = 10 * 1024 * 1024;
+const DEFAULT_LIMIT: i64 = 20;
+const PREVIEW_TOP: usize = 10;
+
+/// Serves the HTTP API until the process is stopped, every request gets its own connection
+pub fn run(config: &Config, bind: &str) -> Result<Value, CliError> {
+    let server = Server::http(bind)
+        .map_err(|error| CliError::Io(format!("Failed to listen on {}, {}", bind, error)))?;
+
+    if config.api_token.is_none() {
+        output::warning(
+            "[No api_token In Profile] ",
+            "Anyone who can reach the server can upload tournaments",
+        );
+    }
+    output::line(
+        format!("[Serving On http://{}, Stop With Ctrl+C]", bind)
+            .green()
+            .bold(),
+    );
+
+    for mut request in server.incoming_requests() {
+        let method = request.method().to_string();
+        let url = request.url().to_owned();
+
+        let (status, body) = match authorize(config, &request) {
+            Err(status) => (
+                status,
+                json!({ "error": "Unauthorized", "message": "Missing or wrong bearer token" }),
+            ),
+            Ok(()) => match handle(config, &mut request) {
+                Ok((status, body)) => (status, body),
+                Err(error) => (
+                    status_code(&error),
+                    json!({ "error": error.to_str(), "message": error.message() }),
+                ),
+            },
+        };
+
+        output::event(
+            "request",
+            json!({ "method": method, "url": url, "status": status }),
+        );
+        output::line(format!(
+            "{}{}",
+            format!("{} {} ", method, url).white(),
+            match status {
+                200..=299 => status.to_string().green(),
+                _ => status.to_string().red(),
+            }
+        ));
+
+        let response = Response::from_string(body.to_string())
+            .with_status_code(status)
+            .with_header(
+                Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..])
+                    .expect("static header is valid"),
+            );
+        if let Err(error) = request.respond(response) {
+            output::warning("[Failed To Respond] ", &error.to_string());
+        }
+    }
+
+    Ok(json!({}))
+}
+
+fn authorize(config: &Config, request: &Request) -> Result<(), u16> {
+    let Some(token) = &config.api_token else {
+        return Ok(());
+    };
+
+    let authorized = request
+        .headers()
+        .iter()
+        .find(|header| header.field.equiv("Authorization"))
+        .and_then(|header| header.value.as_str().strip_prefix("Bearer "))
+        .is_some_and(|given| tokens_match(given, token));
+    match authorized {
+        true => Ok(()),
+        false => Err(401),
+    }
+}
+
+/// Compares the SHA-256 digests of both tokens over every byte, so the time taken doesn't
+/// tell how much of a guessed token was right
+fn tokens_match(given: &str, token: &str) -> bool {
+    let (given, token) = (Sha256::digest(given), Sha256::digest(token));
+
+    given
+        .iter()
+        .zip(token.iter())
+        .fold(0, |diff, (a, b)| diff | (a ^ b))
+        == 0
+}
+
+fn handle(config: &Config, request: &mut Request) -> Result<(u16, Value), CliError> {
+    let (path, query) = match request.url().split_once('?') {
+        Some((path, query)) => (path.to_owned(), parse_query(query)),
+        None => (request.url().to_owned(), HashMap::new()),
+    };
+
+    match (request.method(), path.trim_end_matches('/')) {
+        (Method::Get, "/health") => Ok((200, json!({ "version": env!("CARGO_PKG_VERSION") }))),
+        (Method::Get, "/tournaments") => {
+            let conn = &mut connect(config)?;
+            let tournaments = db::list_tournaments(conn, limit(&query)?)?;
+            Ok((200, json!({ "tournaments": tournaments })))
+        }
+        (Method::Get, "/leaderboard") => {
+            let conn = &mut connect(config)?;
+            let season = match query.get("season") {
+                Some(name) => Some(season::find(conn, name)?),
+                None => None,
+            };
+            match (query.get("stat"), season) {
+                (Some(stat), season) => {
+                    let stat = stat.to_lowercase();
+                    let season_id = season.as_ref().map(|season| season.id);
+                    let users = db::get_stat_leaderboard(conn, &stat, season_id, limit(&query)?)?;
+                    Ok((
+                        200,
+                        json!({ "stat": stat, "season": season, "users": users }),
+                    ))
+                }
+                (None, Some(season)) => {
+                    let users = db::get_season_leaderboard(conn, season.id, limit(&query)?)?;
+                    Ok((200, json!({ "season": season, "users": users })))
+                }
+                (None, None) => {
+                    let users = db::get_leaderboard(conn, limit(&query)?)?;
+                    Ok((200, json!({ "users": users })))
+                }
+            }
+        }
+        (Method::Get, "/seasons") => {
+            let conn = &mut connect(config)?;
+            let seasons = db::get_seasons(conn)?;
+            Ok((200, json!({ "seasons": seasons })))
+        }
+        (Method::Post, "/validate") => {
+            let Some(body) = read_body(request)? else {
+                return Ok(payload_too_large());
+            };
+            let conn = &mut connect(config)?;
+            let mut parsed_tournament = parse_body(config, &query, &body)?;
+            let (resolved, unresolved) = resolve_ids(conn, &mut parsed_tournament)?;
+            let preview = preview::build(conn, &parsed_tournament, PREVIEW_TOP)?;
+
+            Ok((
+                200,
+                json!({
+                    "valid": unresolved.is_empty() && preview.violations.is_empty(),
+                    "preview": preview,
+                    "resolved_players": resolved,
+                    "unresolved_players": unresolved,
+                }),
+            ))
+        }
+        (Method::Post, "/tournaments") => {
+            let Some(body) = read_body(request)? else {
+                return Ok(payload_too_large());
+            };
+            let title = query.get("title").ok_or(CliError::Usage(String::from(
+                "The title query parameter is required",
+            )))?;
+            let mut user_input = input::from_flags(
+                &config.defaults,
+                title.to_owned(),
+                query.get("date").map(|date| date.as_str()),
+                query.get("link").cloned(),
+            )?;
+            user_input.season = query.get("season").cloned();
+
+            let (conn, db_name) =
+                &mut db::establish_connection(&config.database_url, &config.retry_policy)?;
+            let mut parsed_tournament = parse_body(config, &query, &body)?;
+            parsed_tournament.set_user_input(&user_input);
+            let (_, unresolved) = resolve_ids(conn, &mut parsed_tournament)?;
+            if !unresolved.is_empty() {
+                return Err(CliError::Parse(format!(
+                    "Couldn't resolve missing Steam ids:\n{}",
+                    resolve::describe_unresolved(&unresolved)
+                )));
+            }
+
+            let preview = preview::build(conn, &parsed_tournament, PREVIEW_TOP)?;
+            if !preview.violations.is_empty() {
+                return Err(CliError::Parse(format!(
+                    "Bracket progression is inconsistent:\n{}",
+                    preview
+                        .violations
+                        .iter()
+                        .map(|v| format!("{} ({})", v.message, v.players.join(", ")))
+                        .collect::<Vec<String>>()
+                        .join("\n")
+                )));
+            }
+            if db::tournament_exists(conn, &parsed_tournament.title, parsed_tournament.date)? {
+                return Err(CliError::Usage(format!(
+                    "A tournament \"{}\" on {} already exists",
+                    parsed_tournament.title, parsed_tournament.date
+                )));
+            }
+
+            let announcement = webhook::Announcement::new(&parsed_tournament);
+            let entry = AuditEntry::new(config, "serve upload", db_name)
+                .source_content("http request", body.as_bytes());
+            let summary = audit::record_insert(
+                config,
+                entry,
+                db::insert_parsed_tournament(
+                    conn,
+                    parsed_tournament,
+                    &config.scoring,
+                    &config.stat_points,
+                ),
+            )?;
+            webhook::announce(config, &announcement, summary.tournament_id);
+
+            Ok((201, json!({ "preview": preview, "summary": summary })))
+        }
+        _ => Ok((404, json!({ "error": "Not Found", "message": path }))),
+    }
+}
+
+fn status_code(error: &CliError) -> u16 {
+    match error {
+        CliError::Usage(_) => 400,
+        CliError::Parse(_) => 422,
+        CliError::Connection(_) => 503,
+        CliError::Cancelled(_) => 409,
+        CliError::Config(_) | CliError::Database(_) | CliError::Io(_) => 500,
+    }
+}
+
+fn connect(config: &Config) -> Result<MysqlConnection, CliError> {
+    db::establish_connection(&config.database_url, &config.retry_policy).map(|(conn, _)| conn)
+}
+
+fn parse_query(query: &str) -> HashMap<String, String> {
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn limit(query: &HashMap<String, String>) -> Result<i64, CliError> {
+    match query.get("limit") {
+        Some(limit) => limit
+            .parse::<i64>()
+            .ok()
+            .filter(|limit| *limit > 0)
+            .ok_or_else(|| CliError::Usage(format!("Invalid limit '{}'", limit))),
+        None => Ok(DEFAULT_LIMIT),
+    }
+}
+
+/// `None` when the body is larger than `MAX_BODY_BYTES`, it's read one byte past the
+/// limit so a cut off tournament is never parsed
+fn read_body(request: &mut Request) -> Result<Option<String>, CliError> {
+    let mut body = String::new();
+    request
+        .as_reader()
+        .take(MAX_BODY_BYTES + 1)
+        .read_to_string(&mut body)
+        .map_err(|error| CliError::Usage(format!("Failed to read request body, {}", error)))?;
+
+    Ok((body.len() as u64 <= MAX_BODY_BYTES).then_some(body))
+}
+
+fn payload_too_large() -> (u16, Value) {
+    (
+        413,
+        json!({
+            "error": "Payload Too Large",
+            "message": format!("The body is larger than {} bytes", MAX_BODY_BYTES),
+        }),
+    )
+}
+
+/// The body is a tournament file, parsed with the `format` query parameter or the profile's
+fn parse_body(
+    config: &Config,
+    query: &HashMap<String, String>,
+    body: &str,
+) -> Result<ParsedTournament, CliError> {
+    let format = config
+        .formats
+        .get(query.get("format").map(|format| format.as_str()))?;
+
+    tournament_parser::parse_str(body, &format)
+}
+
+/// Resolves missing Steam ids against the file & database, never prompts
+fn resolve_ids(
+    conn: &mut MysqlConnection,
+    parsed_tournament: &mut ParsedTournament,
+) -> Result<(Vec<resolve::Resolved>, Vec<resolve::Unresolved>), CliError> {
+    if resolve::missing_usernames(parsed_tournament).is_empty() {
+        return Ok((vec![], vec![]));
+    }
+
+    let mut known = resolve::file_usernames(parsed_tournament);
+    known.extend(db::get_all_usernames(conn)?);
+
+    Ok(resolve::resolve_missing_ids(
+        parsed_tournament,
+        &known,
+        false,
+    ))
+}
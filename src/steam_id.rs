@@ -0,0 +1,196 @@
+/// SteamID64 of account 0 in the public universe with the individual account type & desktop instance
+const INDIVIDUAL_BASE: u64 = 76561197960265728;
+/// Account ids are 32 bit, everything above is universe, type & instance
+const ACCOUNT_ID_MASK: u64 = 0xFFFF_FFFF;
+
+/// Turns any accepted form of a Steam id into a SteamID64:
+/// - SteamID64 `76561197960287930`
+/// - SteamID2 `STEAM_0:0:11101` (or `STEAM_1:...`)
+/// - SteamID3 `[U:1:22202]` (brackets optional)
+/// - Profile URL `https://steamcommunity.com/profiles/76561197960287930`
+pub fn normalise(id: &str) -> Result<String, String> {
+    let id = id.trim();
+
+    let id64 = if let Some(steam2) = id.strip_prefix("STEAM_") {
+        from_steam2(steam2)
+    } else if id.starts_with("[U:") || id.starts_with("U:") {
+        from_steam3(id.trim_start_matches('[').trim_end_matches(']'))
+    } else if id.starts_with("http://") || id.starts_with("https://") {
+        from_profile_url(id)
+    } else {
+        from_steam64(id)
+    };
+
+    id64.map(|id64| id64.to_string())
+        .map_err(|reason| format!("Invalid Steam id '{}', {}", id, reason))
+}
+
+/// How many `:` a Steam id written at the start of `text` contains,
+/// so it can be told apart from the `:` separating it from the username
+pub fn colon_count(text: &str) -> usize {
+    if text.starts_with("STEAM_") || text.starts_with("[U:") || text.starts_with("U:") {
+        2
+    } else if text.starts_with("http://") || text.starts_with("https://") {
+        1
+    } else {
+        0
+    }
+}
+
+fn from_steam64(id: &str) -> Result<u64, String> {
+    if id.len() != 17 || !id.chars().all(|c| c.is_ascii_digit()) {
+        return Err(String::from("a SteamID64 has 17 digits"));
+    }
+
+    let id64 = id
+        .parse::<u64>()
+        .map_err(|_| String::from("not a number"))?;
+    if id64 & !ACCOUNT_ID_MASK != INDIVIDUAL_BASE & !ACCOUNT_ID_MASK {
+        return Err(String::from(
+            "not a public individual account (should start with 7656119)",
+        ));
+    }
+
+    Ok(id64)
+}
+
+/// `X:Y:Z` after `STEAM_`, account id is `Z * 2 + Y`
+fn from_steam2(id: &str) -> Result<u64, String> {
+    let parts: Vec<&str> = id.split(':').collect();
+    let [universe, y, z] = parts.as_slice() else {
+        return Err(String::from("expected STEAM_X:Y:Z"));
+    };
+    if *universe != "0" && *universe != "1" {
+        return Err(String::from("universe should be 0 or 1"));
+    }
+
+    let y = match *y {
+        "0" => 0,
+        "1" => 1,
+        _ => return Err(String::from("Y should be 0 or 1")),
+    };
+    let z = z
+        .parse::<u64>()
+        .map_err(|_| String::from("Z should be a number"))?;
+
+    let account_id = z.checked_mul(2).and_then(|v| v.checked_add(y));
+    from_account_id(account_id.ok_or_else(out_of_range)?)
+}
+
+/// `U:1:W`, account id is `W`
+fn from_steam3(id: &str) -> Result<u64, String> {
+    let parts: Vec<&str> = id.split(':').collect();
+    let ["U", "1", w] = parts.as_slice() else {
+        return Err(String::from("expected [U:1:W]"));
+    };
+    let w = w
+        .parse::<u64>()
+        .map_err(|_| String::from("W should be a number"))?;
+
+    from_account_id(w)
+}
+
+fn from_profile_url(url: &str) -> Result<u64, String> {
+    let path = url
+        .split_once("steamcommunity.com/")
+        .map(|(_, path)| path.trim_end_matches('/'))
+        .ok_or_else(|| String::from("not a steamcommunity.com URL"))?;
+
+    match path.split_once('/') {
+        Some(("profiles", id)) => from_steam64(id),
+        Some(("id", _)) => Err(String::from(
+            "custom profile URLs can't be resolved, use the SteamID64",
+        )),
+        _ => Err(String::from(
+            "expected steamcommunity.com/profiles/<SteamID64>",
+        )),
+    }
+}
+
+fn from_account_id(account_id: u64) -> Result<u64, String> {
+    if account_id > ACCOUNT_ID_MASK {
+        return Err(out_of_range());
+    }
+
+    INDIVIDUAL_BASE
+        .checked_add(account_id)
+        .ok_or_else(out_of_range)
+}
+
+fn out_of_range() -> String {
+    String::from("account id is out of range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID64: &str = "76561197960287930";
+
+    #[test]
+    fn accepts_steam64() {
+        assert_eq!(normalise(ID64).unwrap(), ID64);
+        assert_eq!(normalise(" 76561197960287930 ").unwrap(), ID64);
+    }
+
+    #[test]
+    fn accepts_steam2() {
+        assert_eq!(normalise("STEAM_0:0:11101").unwrap(), ID64);
+        assert_eq!(normalise("STEAM_1:0:11101").unwrap(), ID64);
+        assert_eq!(normalise("STEAM_0:1:11101").unwrap(), "76561197960287931");
+    }
+
+    #[test]
+    fn accepts_steam3() {
+        assert_eq!(normalise("[U:1:22202]").unwrap(), ID64);
+        assert_eq!(normalise("U:1:22202").unwrap(), ID64);
+    }
+
+    #[test]
+    fn accepts_profile_urls() {
+        assert_eq!(
+            normalise("https://steamcommunity.com/profiles/76561197960287930").unwrap(),
+            ID64
+        );
+        assert_eq!(
+            normalise("http://steamcommunity.com/profiles/76561197960287930/").unwrap(),
+            ID64
+        );
+        assert!(normalise("https://steamcommunity.com/id/gabelogannewell").is_err());
+        assert!(normalise("https://example.com/profiles/76561197960287930").is_err());
+    }
+
+    #[test]
+    fn rejects_overflowing_account_ids() {
+        let error = normalise("STEAM_0:1:18446744073709551615").unwrap_err();
+        assert!(error.contains("out of range"), "{}", error);
+        assert!(normalise("STEAM_0:0:9223372036854775808").is_err());
+        assert!(normalise("STEAM_0:0:4294967296").is_err());
+        assert!(normalise("[U:1:18446744073709551615]").is_err());
+        assert!(normalise("[U:1:4294967296]").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_ids() {
+        assert!(normalise("").is_err());
+        assert!(normalise("7656119796028793").is_err());
+        assert!(normalise("12345678901234567").is_err());
+        assert!(normalise("STEAM_2:0:11101").is_err());
+        assert!(normalise("STEAM_0:2:11101").is_err());
+        assert!(normalise("STEAM_0:0").is_err());
+        assert!(normalise("[U:2:22202]").is_err());
+        assert!(normalise("[G:1:22202]").is_err());
+        assert!(normalise("Rat_96").is_err());
+    }
+
+    #[test]
+    fn counts_colons_of_ids() {
+        assert_eq!(colon_count("STEAM_0:0:11101:Rat_96"), 2);
+        assert_eq!(colon_count("[U:1:22202]:Rat_96"), 2);
+        assert_eq!(
+            colon_count("https://steamcommunity.com/profiles/1:Rat_96"),
+            1
+        );
+        assert_eq!(colon_count("76561197960287930:Rat_96"), 0);
+    }
+}
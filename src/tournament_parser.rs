@@ -1,4 +1,5 @@
 use std::{
+    collections::{BTreeMap, HashMap},
     fs::File,
     io::{self, BufRead, BufReader},
     str::FromStr,
@@ -6,24 +7,56 @@ use std::{
 
 use chrono::{NaiveDateTime, Utc};
 
-use crate::input::UserInput;
+use crate::error::CliError;
+use crate::input::{self, UserInput};
+use crate::rounds::{Round, RoundFormat};
+use crate::steam_id;
 
 #[derive(Debug)]
 pub struct ParsedTournament {
     pub version: i32,
+    /// The round structure the match headers were read with
+    pub format: RoundFormat,
     pub matches: Vec<Match>,
     pub date: NaiveDateTime,
     pub title: String,
     pub link: Option<String>,
+    /// Name of the season to file it under, otherwise the season its date falls in
+    pub season: Option<String>,
+    /// `!key: value` lines above the first match
+    pub header: TournamentHeader,
+}
+
+/// Tournament details a file can carry itself, used by `watch` instead of prompting
+#[derive(Debug, Clone, Default)]
+pub struct TournamentHeader {
+    pub title: Option<String>,
+    pub date: Option<NaiveDateTime>,
+    pub link: Option<String>,
+    pub season: Option<String>,
 }
 
 #[derive(Debug, Clone)]
 pub struct Match {
-    pub match_type: MatchType,
+    pub match_type: Round,
     pub players: Vec<MatchUser>,
+    /// Empty in solo matches
+    pub teams: Vec<MatchTeam>,
+    pub info: MatchInfo,
     pub match_index: i32,
 }
 
+/// Optional details from the `!key: value` lines of a match
+#[derive(Debug, Clone, Default)]
+pub struct MatchInfo {
+    /// Map or minigame name
+    pub map: Option<String>,
+    pub mode: Option<String>,
+    pub started_at: Option<NaiveDateTime>,
+    pub duration_secs: Option<i32>,
+    pub notes: Option<String>,
+}
+
 #[derive(Debug, Clone)]
 pub struct MatchUser {
     pub username: String,
@@ -31,65 +64,45 @@ pub struct MatchUser {
     pub rank: i32,
     pub life_status: LifeStatus,
     pub immune_status: ImmuneStatus,
+    /// Name of the player's team in this match
+    pub team: Option<String>,
+    /// Extra `|name=value` fields after the username, like score or kills
+    pub stats: BTreeMap<String, i32>,
+    /// The id as written when it isn't a valid Steam id, `user_id` is then left empty
+    /// so the player is resolved from the username like one without an id
+    pub invalid_id: Option<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
-pub enum MatchType {
-    Final,
-    SemiFinal,
-    QuarterFinal,
-    Bonus,
-    Game4,
-    Game3,
-    Game2,
-    Game1,
+#[derive(Debug, Clone)]
+pub struct MatchTeam {
+    pub name: String,
+    pub rank: i32,
+    pub life_status: LifeStatus,
 }
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum LifeStatus {
     Alive,
     Eliminated,
     Playing,
 }
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum ImmuneStatus {
     Immune,
     Saved,
     None,
 }
 
-impl MatchType {
-    pub fn to_str(&self) -> &'static str {
-        match self {
-            MatchType::Final => "final",
-            MatchType::SemiFinal => "semifinal",
-            MatchType::QuarterFinal => "quarterfinal",
-            MatchType::Bonus => "bonus",
-            MatchType::Game4 => "game_4",
-            MatchType::Game3 => "game_3",
-            MatchType::Game2 => "game_2",
-            MatchType::Game1 => "game_1",
-        }
-    }
-}
-impl FromStr for MatchType {
-    type Err = ();
-
-    fn from_str(input: &str) -> Result<Self, Self::Err> {
-        match input {
-            "Final" => Ok(MatchType::Final),
-            "Semifinal" => Ok(MatchType::SemiFinal),
-            "Quarterfinal" => Ok(MatchType::QuarterFinal),
-            "Bonus" => Ok(MatchType::Bonus),
-            "Game 4" => Ok(MatchType::Game4),
-            "Game 3" => Ok(MatchType::Game3),
-            "Game 2" => Ok(MatchType::Game2),
-            "Game 1" => Ok(MatchType::Game1),
-            _ => Err(()),
+impl LifeStatus {
+    pub fn from_key(key: &str) -> Option<LifeStatus> {
+        match key {
+            "alive" => Some(LifeStatus::Alive),
+            "eliminated" => Some(LifeStatus::Eliminated),
+            "playing" => Some(LifeStatus::Playing),
+            _ => None,
         }
     }
-}
 
-impl LifeStatus {
     pub fn to_str(&self) -> &'static str {
         match self {
             LifeStatus::Alive => "alive",
@@ -112,6 +125,15 @@ impl FromStr for LifeStatus {
 }
 
 impl ImmuneStatus {
+    pub fn from_key(key: &str) -> Option<ImmuneStatus> {
+        match key {
+            "immune" => Some(ImmuneStatus::Immune),
+            "saved" => Some(ImmuneStatus::Saved),
+            "none" => Some(ImmuneStatus::None),
+            _ => None,
+        }
+    }
+
     pub fn to_str(&self) -> &'static str {
         match self {
             ImmuneStatus::Immune => "immune",
@@ -133,51 +155,168 @@ impl FromStr for ImmuneStatus {
     }
 }
 
-pub fn parse(file_path: &String, user_input: &UserInput) -> ParsedTournament {
+impl ParsedTournament {
+    pub fn set_user_input(&mut self, user_input: &UserInput) {
+        self.date = user_input.date;
+        self.title = user_input.title.to_owned();
+        self.link = user_input.link.to_owned();
+        if user_input.season.is_some() {
+            self.season = user_input.season.to_owned();
+        }
+    }
+
+    pub fn has_teams(&self) -> bool {
+        self.matches.iter().any(|m| !m.teams.is_empty())
+    }
+
+    /// Extra ranking points per player from their stats over all matches,
+    /// players that don't get any are left out
+    pub fn stat_points(&self, stat_points: &HashMap<String, i32>) -> Vec<(&MatchUser, i32)> {
+        let mut points: Vec<(&MatchUser, i32)> = vec![];
+        for user in self.matches.iter().flat_map(|m| &m.players) {
+            let user_points: i32 = user
+                .stats
+                .iter()
+                .filter_map(|(name, value)| stat_points.get(name).map(|p| value * p))
+                .sum();
+            match points.iter_mut().find(|(u, _)| u.user_id == user.user_id) {
+                Some((_, total)) => *total += user_points,
+                None => points.push((user, user_points)),
+            }
+        }
+        points.retain(|(_, points)| *points != 0);
+
+        points
+    }
+
+    /// Names of every stat given on any player line, sorted
+    pub fn stat_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .matches
+            .iter()
+            .flat_map(|m| &m.players)
+            .flat_map(|user| user.stats.keys().cloned())
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+}
+
+/// Parses the matches of a tournament file, the title, date & link are set afterwards
+/// with `set_user_input`. Every `#` header has to be a round of `format`
+pub fn parse(file_path: &str, format: &RoundFormat) -> Result<ParsedTournament, CliError> {
+    let lines = get_lines(file_path)
+        .map_err(|error| CliError::Io(format!("Failed to read {}, {}", file_path, error)))?;
+
+    parse_lines(&lines, format)
+}
+
+/// Same as `parse` for the content of a tournament file, e.g. from an HTTP request body
+pub fn parse_str(content: &str, format: &RoundFormat) -> Result<ParsedTournament, CliError> {
+    let lines: Vec<String> = content.lines().map(|line| line.to_owned()).collect();
+
+    parse_lines(&lines, format)
+}
+
+fn parse_lines(lines: &[String], format: &RoundFormat) -> Result<ParsedTournament, CliError> {
     let mut tournament = ParsedTournament {
         version: -1,
+        format: format.to_owned(),
         matches: vec![],
         date: Utc::now().naive_local(),
         title: String::new(),
         link: None,
+        season: None,
+        header: TournamentHeader::default(),
     };
 
-    let lines = get_lines(&file_path).expect("Failed to get lines");
-    tournament.version = lines[0]
+    let version_line = lines
+        .first()
+        .ok_or(CliError::Parse(String::from("Empty tournament file")))?;
+    tournament.version = version_line
+        .trim()
         .replace("v", "")
         .parse::<i32>()
-        .expect("Failed to parse version");
-    tournament.date = user_input.date;
-    tournament.title = user_input.title.to_owned();
-    tournament.link = user_input.link.to_owned();
-
-    for mut i in 0..lines.len() {
-        let raw_line = lines[i].to_owned();
-        let line = raw_line.trim();
-        if raw_line.is_empty() {
+        .map_err(|_| parse_error(1, &format!("Failed to parse version '{}'", version_line)))?;
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i].trim();
+        i += 1;
+        if line.is_empty() {
             continue;
         }
 
-        let match_type = get_match_type(line);
-
-        if match_type.is_none() {
+        if !line.starts_with('#') {
+            if let Some(header_line) = line.strip_prefix('!') {
+                if tournament.matches.is_empty() {
+                    parse_header(&mut tournament.header, header_line)
+                        .map_err(|error| parse_error(i, &error))?;
+                }
+            }
             continue;
         }
+        let match_type = format.by_header(line).cloned().ok_or_else(|| {
+            parse_error(
+                i,
+                &format!(
+                    "Unknown round '{}', expected one of: {}",
+                    line,
+                    format.names()
+                ),
+            )
+        })?;
 
+        let header_line = i;
         let mut tournament_match: Match = Match {
-            match_type: match_type.unwrap(),
+            match_type,
             players: vec![],
+            teams: vec![],
+            info: MatchInfo::default(),
             match_index: -1,
         };
 
-        i += 1;
-        let mut player_line = lines[i].trim().to_owned();
-        while !player_line.is_empty() {
-            let player: MatchUser = parse_player(&player_line);
-            tournament_match.players.push(player);
+        while let Some(player_line) = lines.get(i).map(|l| l.trim()) {
+            if player_line.is_empty() {
+                break;
+            }
 
+            if let Some(info_line) = player_line.strip_prefix('!') {
+                parse_match_info(&mut tournament_match.info, info_line)
+                    .map_err(|error| parse_error(i + 1, &error))?;
+                i += 1;
+                continue;
+            }
+
+            // Team lines start with '@', the player lines below them are its members
+            if let Some(team_line) = player_line.strip_prefix('@') {
+                let team = parse_team(team_line).map_err(|error| parse_error(i + 1, &error))?;
+                if tournament_match.teams.iter().any(|t| t.name == team.name) {
+                    return Err(parse_error(
+                        i + 1,
+                        &format!("Team '{}' is listed twice in this match", team.name),
+                    ));
+                }
+                tournament_match.teams.push(team);
+                i += 1;
+                continue;
+            }
+
+            let mut player: MatchUser =
+                parse_player(player_line).map_err(|error| parse_error(i + 1, &error))?;
+            player.team = tournament_match.teams.last().map(|t| t.name.to_owned());
+            tournament_match.players.push(player);
             i += 1;
-            player_line = lines[i].trim().to_owned();
+        }
+
+        if !tournament_match.teams.is_empty()
+            && tournament_match.players.iter().any(|p| p.team.is_none())
+        {
+            return Err(parse_error(
+                header_line,
+                "Every player of a team match has to be below a team line",
+            ));
         }
 
         tournament.matches.push(tournament_match);
@@ -188,16 +327,29 @@ pub fn parse(file_path: &String, user_input: &UserInput) -> ParsedTournament {
         tournament.matches[i].match_index = i as i32;
     }
 
-    tournament
+    if tournament.matches.is_empty() {
+        return Err(CliError::Parse(String::from(
+            "No matches found in tournament file",
+        )));
+    }
+    tournament.season = tournament.header.season.to_owned();
+
+    Ok(tournament)
 }
 
-pub fn get_overall_player_list(matches: Vec<Match>) -> Vec<MatchUser> {
+/// Players in placement order, walking the matches in file order which lists the final first.
+/// Alive players only count in the final, eliminations count in every round
+pub fn get_overall_player_list(tournament: &ParsedTournament) -> Vec<MatchUser> {
+    let final_round = tournament.format.final_round();
+
     let mut players: Vec<MatchUser> = vec![];
-    for m in matches {
+    for m in &tournament.matches {
+        let is_final = final_round == Some(&m.match_type);
         let mut new_players: Vec<MatchUser> = m
             .players
-            .into_iter()
-            .filter(|p| p.life_status != LifeStatus::Alive || m.match_type == MatchType::Final)
+            .iter()
+            .filter(|p| is_placed(&p.life_status, is_final))
+            .cloned()
             .collect();
 
         players.append(&mut new_players);
@@ -206,30 +358,370 @@ pub fn get_overall_player_list(matches: Vec<Match>) -> Vec<MatchUser> {
     players
 }
 
+/// Players grouped per placement, best first. Solo tournaments have one player per placement,
+/// team tournaments place the members of a team together using the team's result
+pub fn get_placements(tournament: &ParsedTournament) -> Vec<Vec<MatchUser>> {
+    if !tournament.has_teams() {
+        return get_overall_player_list(tournament)
+            .into_iter()
+            .map(|player| vec![player])
+            .collect();
+    }
+
+    let final_round = tournament.format.final_round();
+
+    let mut placements: Vec<Vec<MatchUser>> = vec![];
+    for m in &tournament.matches {
+        let is_final = final_round == Some(&m.match_type);
+        for team in &m.teams {
+            if !is_placed(&team.life_status, is_final) {
+                continue;
+            }
+
+            placements.push(
+                m.players
+                    .iter()
+                    .filter(|p| p.team.as_ref() == Some(&team.name))
+                    .cloned()
+                    .collect(),
+            );
+        }
+    }
+
+    placements
+}
+
+fn is_placed(life_status: &LifeStatus, is_final: bool) -> bool {
+    *life_status != LifeStatus::Alive || is_final
+}
+
+/// Every player once, in file order, so the name from the latest match is kept
+pub fn get_unique_players(matches: &[Match]) -> Vec<MatchUser> {
+    let mut players: Vec<MatchUser> = vec![];
+    for m in matches {
+        for player in &m.players {
+            if !players.iter().any(|p| p.user_id == player.user_id) {
+                players.push(player.clone());
+            }
+        }
+    }
+
+    players
+}
+
 fn get_lines(filename: &str) -> io::Result<Vec<String>> {
     BufReader::new(File::open(filename)?).lines().collect()
 }
 
-fn get_match_type(line: &str) -> Option<MatchType> {
-    let match_line = &line.replace("#", "");
+fn parse_error(line_number: usize, message: &str) -> CliError {
+    CliError::Parse(format!("Line {}: {}", line_number, message))
+}
+
+/// `key: value` after the '!', notes may be given on several lines
+fn parse_header(header: &mut TournamentHeader, header_line: &str) -> Result<(), String> {
+    let (key, value) = header_line
+        .split_once(':')
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .ok_or_else(|| format!("Expected '!key: value', got '!{}'", header_line))?;
+    if value.is_empty() {
+        return Err(format!("'!{}' has no value", key));
+    }
+
+    let already_set = match key {
+        "title" => header.title.replace(value.to_owned()).is_some(),
+        "date" => {
+            let date = input::parse_date(value).map_err(|_| {
+                format!(
+                    "Failed to parse date '{}', expected YYYY-MM-DD HH:MM",
+                    value
+                )
+            })?;
+            header.date.replace(date).is_some()
+        }
+        "link" => header.link.replace(value.to_owned()).is_some(),
+        "season" => header.season.replace(value.to_owned()).is_some(),
+        _ => {
+            return Err(format!(
+                "Unknown tournament detail '!{}', expected one of: title, date, link, season",
+                key
+            ))
+        }
+    };
+    if already_set {
+        return Err(format!("'!{}' is given twice", key));
+    }
+
+    Ok(())
+}
+
+fn parse_match_info(info: &mut MatchInfo, info_line: &str) -> Result<(), String> {
+    let (key, value) = info_line
+        .split_once(':')
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .ok_or_else(|| format!("Expected '!key: value', got '!{}'", info_line))?;
+    if value.is_empty() {
+        return Err(format!("'!{}' has no value", key));
+    }
+
+    let already_set = match key {
+        "map" => info.map.replace(value.to_owned()).is_some(),
+        "mode" => info.mode.replace(value.to_owned()).is_some(),
+        "start" => {
+            let started_at = input::parse_date(value).map_err(|_| {
+                format!(
+                    "Failed to parse start '{}', expected YYYY-MM-DD HH:MM",
+                    value
+                )
+            })?;
+            info.started_at.replace(started_at).is_some()
+        }
+        "duration" => {
+            let duration_secs = parse_duration(value).ok_or_else(|| {
+                format!(
+                    "Failed to parse duration '{}', expected e.g. 12:30 or 12m30s",
+                    value
+                )
+            })?;
+            info.duration_secs.replace(duration_secs).is_some()
+        }
+        "notes" => {
+            info.notes = Some(match info.notes.take() {
+                Some(notes) => format!("{}\n{}", notes, value),
+                None => value.to_owned(),
+            });
+            false
+        }
+        _ => {
+            return Err(format!(
+                "Unknown match detail '!{}', expected one of: map, mode, start, duration, notes",
+                key
+            ))
+        }
+    };
+    if already_set {
+        return Err(format!("'!{}' is given twice for this match", key));
+    }
+
+    Ok(())
+}
+
+/// `h:mm:ss`, `mm:ss` or `1h2m3s`, a bare number is seconds
+fn parse_duration(value: &str) -> Option<i32> {
+    if value.contains(':') {
+        return value.split(':').try_fold(0i32, |total, part| {
+            total
+                .checked_mul(60)?
+                .checked_add(part.trim().parse::<i32>().ok()?)
+        });
+    }
+
+    let mut total = 0i32;
+    let mut number = String::new();
+    for c in value.chars().filter(|c| !c.is_whitespace()) {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        total = total.checked_add(number.parse::<i32>().ok()?.checked_mul(unit)?)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        total = total.checked_add(number.parse::<i32>().ok()?)?;
+    }
+
+    Some(total)
+}
 
-    let match_type = MatchType::from_str(&match_line);
-    if match_type.is_err() {
-        None
-    } else {
-        Some(match_type.unwrap())
+/// `life:rank:team name`, after the '@'
+fn parse_team(team_line: &str) -> Result<MatchTeam, String> {
+    let parts: Vec<&str> = team_line.splitn(3, ':').collect();
+    let [life_status, rank, name] = parts.as_slice() else {
+        return Err(format!(
+            "Expected '@life:rank:team name', got '@{}'",
+            team_line
+        ));
+    };
+    if name.trim().is_empty() {
+        return Err(String::from("Team has no name"));
     }
+
+    Ok(MatchTeam {
+        name: name.trim().to_owned(),
+        rank: rank
+            .parse::<i32>()
+            .map_err(|_| format!("Failed to parse team rank '{}'", rank))?,
+        life_status: LifeStatus::from_str(life_status)
+            .map_err(|_| format!("Failed to parse team life status '{}'", life_status))?,
+    })
 }
 
-fn parse_player(player_line: &str) -> MatchUser {
-    let parts: Vec<String> = player_line.splitn(5, ':').map(|p| p.to_owned()).collect();
-    MatchUser {
-        username: parts[4].to_owned(),
-        user_id: parts[3].to_owned(),
+/// The id may be any form `steam_id::normalise` accepts, it's stored as a SteamID64.
+/// It may also be left empty (`:e:3::Username`), it's then resolved from the username
+/// with `resolve::resolve_missing_ids` before uploading, as is an id that isn't valid.
+/// Stats follow the username as `|name=value` fields (`:a:1:<id>:Username|score=10|kills=2`)
+fn parse_player(player_line: &str) -> Result<MatchUser, String> {
+    let parts: Vec<&str> = player_line.splitn(4, ':').collect();
+    // SteamID2, SteamID3 & URLs contain ':' themselves
+    let id_end = parts
+        .get(3)
+        .and_then(|rest| rest.match_indices(':').nth(steam_id::colon_count(rest)))
+        .map(|(i, _)| i);
+    let (Some(rest), Some(id_end)) = (parts.get(3), id_end) else {
+        return Err(format!(
+            "Expected 'immune:life:rank:id:username', got '{}'",
+            player_line
+        ));
+    };
+    let (user_id, username) = (rest[..id_end].trim(), &rest[id_end + 1..]);
+    let (username, stats) = split_stats(username)?;
+    if user_id.is_empty() && username.trim().is_empty() {
+        return Err(String::from("Player has neither an id nor a username"));
+    }
+    let (user_id, invalid_id) = match steam_id::normalise(user_id) {
+        _ if user_id.is_empty() => (String::new(), None),
+        Ok(id64) => (id64, None),
+        // Without a username there is nothing to resolve it from
+        Err(reason) if username.trim().is_empty() => return Err(reason),
+        Err(_) => (String::new(), Some(user_id.to_owned())),
+    };
+
+    Ok(MatchUser {
+        username: username.to_owned(),
+        user_id,
         rank: parts[2]
             .parse::<i32>()
-            .expect("Failed to parse player rank"),
-        life_status: LifeStatus::from_str(&parts[1]).expect("Failed to Parse LifeStatus"),
-        immune_status: ImmuneStatus::from_str(&parts[0]).expect("Failed to Parse ImmuneStatus"),
+            .map_err(|_| format!("Failed to parse player rank '{}'", parts[2]))?,
+        life_status: LifeStatus::from_str(parts[1])
+            .map_err(|_| format!("Failed to parse life status '{}'", parts[1]))?,
+        immune_status: ImmuneStatus::from_str(parts[0])
+            .map_err(|_| format!("Failed to parse immune status '{}'", parts[0]))?,
+        team: None,
+        stats,
+        invalid_id,
+    })
+}
+
+/// Splits the trailing `|name=value` fields off a username, values are whole numbers or
+/// durations (stored in seconds). Segments are only taken as stats from the end while they
+/// parse as one, so a `|` in the name itself stays part of it
+fn split_stats(username: &str) -> Result<(&str, BTreeMap<String, i32>), String> {
+    let mut end = username.len();
+    let mut fields: Vec<(String, i32)> = vec![];
+    while let Some(i) = username[..end].rfind('|') {
+        let Some(stat) = parse_stat(&username[i + 1..end]) else {
+            break;
+        };
+        fields.push(stat);
+        end = i;
+    }
+
+    let mut stats: BTreeMap<String, i32> = BTreeMap::new();
+    for (name, value) in fields.into_iter().rev() {
+        if stats.contains_key(&name) {
+            return Err(format!("Stat '{}' is given twice", name));
+        }
+        stats.insert(name, value);
+    }
+
+    Ok((&username[..end], stats))
+}
+
+/// A `name=value` field with a name of letters, digits & '_'
+fn parse_stat(field: &str) -> Option<(String, i32)> {
+    let (name, value) = field.split_once('=')?;
+    let (name, value) = (name.trim(), value.trim());
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let value = value
+        .parse::<i32>()
+        .ok()
+        .or_else(|| parse_duration(value))?;
+
+    Some((name.to_lowercase(), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stats(fields: &[(&str, i32)]) -> BTreeMap<String, i32> {
+        fields
+            .iter()
+            .map(|(name, value)| (name.to_string(), *value))
+            .collect()
+    }
+
+    #[test]
+    fn reads_trailing_stats() {
+        assert_eq!(
+            split_stats("Rat_96|kills=7|damage=1240|survived=11:02").unwrap(),
+            (
+                "Rat_96",
+                stats(&[("kills", 7), ("damage", 1240), ("survived", 662)])
+            )
+        );
+        assert_eq!(
+            split_stats("Rat_96|Kills = 7").unwrap(),
+            ("Rat_96", stats(&[("kills", 7)]))
+        );
+        assert_eq!(split_stats("Rat_96").unwrap(), ("Rat_96", stats(&[])));
+    }
+
+    #[test]
+    fn keeps_pipes_and_equals_in_names() {
+        assert_eq!(split_stats("Rat|96").unwrap(), ("Rat|96", stats(&[])));
+        assert_eq!(split_stats("a=b").unwrap(), ("a=b", stats(&[])));
+        assert_eq!(
+            split_stats("Cool=Guy|kills=2").unwrap(),
+            ("Cool=Guy", stats(&[("kills", 2)]))
+        );
+        assert_eq!(
+            split_stats("|Rat|96||kills=3").unwrap(),
+            ("|Rat|96|", stats(&[("kills", 3)]))
+        );
+    }
+
+    #[test]
+    fn leaves_malformed_stats_in_the_name() {
+        assert_eq!(
+            split_stats("Rat_96|kills=lots").unwrap(),
+            ("Rat_96|kills=lots", stats(&[]))
+        );
+        assert_eq!(
+            split_stats("Rat_96|kills").unwrap(),
+            ("Rat_96|kills", stats(&[]))
+        );
+        assert_eq!(
+            split_stats("Rat_96|bad name=2").unwrap(),
+            ("Rat_96|bad name=2", stats(&[]))
+        );
+        // Only the fields after the last malformed one are stats
+        assert_eq!(
+            split_stats("Rat_96|kills=2|oops|score=10").unwrap(),
+            ("Rat_96|kills=2|oops", stats(&[("score", 10)]))
+        );
+    }
+
+    #[test]
+    fn rejects_a_stat_given_twice() {
+        assert!(split_stats("Rat_96|kills=2|Kills=3").is_err());
+    }
+
+    #[test]
+    fn parses_player_lines_with_stats() {
+        let player = parse_player("i:a:1:76561198137705581:Rat|96|kills=7").unwrap();
+        assert_eq!(player.username, "Rat|96");
+        assert_eq!(player.user_id, "76561198137705581");
+        assert_eq!(player.stats, stats(&[("kills", 7)]));
+        assert_eq!(player.immune_status, ImmuneStatus::Immune);
+        assert_eq!(player.life_status, LifeStatus::Alive);
     }
 }
@@ -0,0 +1,82 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use chrono::Local;
+
+use crate::error::CliError;
+
+pub const DONE_DIR_NAME: &str = "done";
+pub const FAILED_DIR_NAME: &str = "failed";
+
+/// Files directly in `dir`, sorted by name. Hidden files & the done/failed folders are left out
+pub fn pending_files(dir: &Path) -> Result<Vec<PathBuf>, CliError> {
+    let entries = fs::read_dir(dir)
+        .map_err(|error| CliError::Io(format!("Failed to read {}, {}", dir.display(), error)))?;
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| !name.starts_with('.'))
+        })
+        .collect();
+    files.sort();
+
+    Ok(files)
+}
+
+/// Moves a processed file into `<dir>/<subdir>`, adding the time to its name if a file
+/// with the same name was processed before
+pub fn move_to(path: &Path, dir: &Path, subdir: &str) -> Result<PathBuf, CliError> {
+    let target_dir = dir.join(subdir);
+    fs::create_dir_all(&target_dir).map_err(|error| {
+        CliError::Io(format!(
+            "Failed to create {}, {}",
+            target_dir.display(),
+            error
+        ))
+    })?;
+
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let mut target = target_dir.join(&file_name);
+    if target.exists() {
+        target = target_dir.join(format!(
+            "{}.{}",
+            file_name,
+            Local::now().format("%Y%m%d%H%M%S")
+        ));
+    }
+
+    fs::rename(path, &target).map_err(|error| {
+        CliError::Io(format!(
+            "Failed to move {} to {}, {}",
+            path.display(),
+            target.display(),
+            error
+        ))
+    })?;
+
+    Ok(target)
+}
+
+/// Writes why a file failed next to it as `<file>.error`
+pub fn write_reason(failed_file: &Path, reason: &str) -> Result<(), CliError> {
+    let mut reason_file = failed_file.as_os_str().to_owned();
+    reason_file.push(".error");
+
+    fs::write(&reason_file, format!("{}\n", reason)).map_err(|error| {
+        CliError::Io(format!(
+            "Failed to write {}, {}",
+            Path::new(&reason_file).display(),
+            error
+        ))
+    })
+}
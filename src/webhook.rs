@@ -0,0 +1,228 @@
+use std::time::Duration;
+
+use colored::Colorize;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+use crate::config::Config;
+use crate::error::CliError;
+use crate::output;
+use crate::tournament_parser::{self, ParsedTournament};
+
+const PODIUM_SIZE: usize = 3;
+const MEDALS: [&str; PODIUM_SIZE] = ["🥇", "🥈", "🥉"];
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// What gets posted about a tournament, built before the upload since that consumes it
+#[derive(Serialize, Debug)]
+pub struct Announcement {
+    pub title: String,
+    pub date: String,
+    pub link: Option<String>,
+    pub winner: Option<String>,
+    /// First 3 placements, team names in team tournaments
+    pub podium: Vec<String>,
+    pub players: usize,
+}
+
+impl Announcement {
+    pub fn new(tournament: &ParsedTournament) -> Announcement {
+        let podium: Vec<String> = tournament_parser::get_placements(tournament)
+            .iter()
+            .take(PODIUM_SIZE)
+            .filter_map(|users| users.first())
+            .map(|user| user.team.to_owned().unwrap_or(user.username.to_owned()))
+            .collect();
+
+        Announcement {
+            title: tournament.title.to_owned(),
+            date: tournament.date.format("%Y-%m-%d %H:%M").to_string(),
+            link: tournament.link.to_owned(),
+            winner: podium.first().cloned(),
+            podium,
+            players: tournament_parser::get_unique_players(&tournament.matches).len(),
+        }
+    }
+
+    /// A Discord webhook message with a single embed
+    pub fn payload(&self, tournament_id: i32) -> Value {
+        let podium = self
+            .podium
+            .iter()
+            .zip(MEDALS)
+            .map(|(name, medal)| format!("{} {}", medal, name))
+            .collect::<Vec<String>>()
+            .join("\n");
+
+        let mut embed = json!({
+            "title": self.title,
+            "description": match &self.winner {
+                Some(winner) => format!("🏆 **{}** won the tournament!", winner),
+                None => String::from("The tournament is over!"),
+            },
+            "fields": [
+                { "name": "Podium", "value": podium, "inline": false },
+                { "name": "Players", "value": self.players.to_string(), "inline": true },
+                { "name": "Date", "value": self.date, "inline": true },
+            ],
+            "footer": { "text": format!("Tournament #{}", tournament_id) },
+        });
+        if let Some(link) = &self.link {
+            embed["url"] = json!(link);
+        }
+
+        json!({ "embeds": [embed] })
+    }
+}
+
+/// Posts the announcement to the profile's `webhook_url`, does nothing without one.
+/// The tournament is already uploaded at this point so a failure only warns.
+pub fn announce(config: &Config, announcement: &Announcement, tournament_id: i32) {
+    let Some(url) = &config.webhook_url else {
+        return;
+    };
+
+    match post(url, &announcement.payload(tournament_id)) {
+        Ok(()) => {
+            output::event("announced", json!({ "tournament_id": tournament_id }));
+            output::line("Posted Results To Webhook".green());
+        }
+        Err(error) => output::warning("[Failed To Post Results To Webhook] ", error.message()),
+    }
+}
+
+pub fn post(url: &str, payload: &Value) -> Result<(), CliError> {
+    ureq::AgentBuilder::new()
+        .timeout(TIMEOUT)
+        .build()
+        .post(url)
+        .send_json(payload)
+        .map_err(|error| CliError::Connection(redact(&error.to_string(), url)))?;
+
+    Ok(())
+}
+
+/// Discord webhook urls end in the webhook's secret token
+fn redact(message: &str, url: &str) -> String {
+    match url.trim_end_matches('/').rsplit_once('/') {
+        Some((_, token)) if !token.is_empty() => message.replace(token, "***"),
+        _ => message.to_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, sync::mpsc, thread};
+
+    use tiny_http::{Response, Server};
+
+    use super::*;
+    use crate::config::ScoringSystem;
+    use crate::db::RetryPolicy;
+
+    const TOKEN: &str = "s3cr3t-webhook-token";
+
+    fn announcement() -> Announcement {
+        Announcement {
+            title: String::from("Autumn Cup"),
+            date: String::from("2026-10-19 20:00"),
+            link: Some(String::from("https://youtu.be/example")),
+            winner: Some(String::from("Rat_96")),
+            podium: vec![
+                String::from("Rat_96"),
+                String::from("eke0909"),
+                String::from("Kattalina9"),
+            ],
+            players: 99,
+        }
+    }
+
+    fn config(webhook_url: Option<String>) -> Config {
+        Config {
+            path: None,
+            profile_name: None,
+            database_url: String::new(),
+            retry_policy: RetryPolicy::from_env(),
+            scoring: ScoringSystem::default(),
+            stat_points: HashMap::new(),
+            defaults: Default::default(),
+            operator: String::new(),
+            audit_log: Default::default(),
+            formats: Default::default(),
+            api_token: None,
+            webhook_url,
+        }
+    }
+
+    /// A local webhook answering every request with `status`, the request bodies are sent
+    /// back over the channel
+    fn mock_webhook(status: u16) -> (String, mpsc::Receiver<String>) {
+        let server = Server::http("127.0.0.1:0").unwrap();
+        let url = format!(
+            "http://{}/api/webhooks/1234/{}",
+            server.server_addr().to_ip().unwrap(),
+            TOKEN
+        );
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let _ = request.respond(Response::empty(status));
+                if sender.send(body).is_err() {
+                    break;
+                }
+            }
+        });
+
+        (url, receiver)
+    }
+
+    #[test]
+    fn posts_the_payload() {
+        let (url, requests) = mock_webhook(204);
+
+        post(&url, &announcement().payload(42)).unwrap();
+
+        let body: Value = serde_json::from_str(&requests.recv_timeout(TIMEOUT).unwrap()).unwrap();
+        let embed = &body["embeds"][0];
+        assert_eq!(embed["title"], "Autumn Cup");
+        assert_eq!(embed["url"], "https://youtu.be/example");
+        assert_eq!(embed["description"], "🏆 **Rat_96** won the tournament!");
+        assert_eq!(
+            embed["fields"][0]["value"],
+            "🥇 Rat_96\n🥈 eke0909\n🥉 Kattalina9"
+        );
+        assert_eq!(embed["fields"][1]["value"], "99");
+        assert_eq!(embed["footer"]["text"], "Tournament #42");
+    }
+
+    #[test]
+    fn redacts_the_token_from_errors() {
+        let (url, _requests) = mock_webhook(500);
+
+        let error = post(&url, &announcement().payload(42)).unwrap_err();
+
+        assert!(matches!(error, CliError::Connection(_)));
+        assert!(!error.message().contains(TOKEN), "{}", error.message());
+        assert!(error.message().contains("***"), "{}", error.message());
+    }
+
+    #[test]
+    fn announce_without_url_posts_nothing() {
+        let (_url, requests) = mock_webhook(204);
+
+        announce(&config(None), &announcement(), 42);
+
+        assert!(requests.recv_timeout(Duration::from_millis(200)).is_err());
+    }
+
+    #[test]
+    fn announce_posts_to_the_url() {
+        let (url, requests) = mock_webhook(204);
+
+        announce(&config(Some(url)), &announcement(), 42);
+
+        assert!(requests.recv_timeout(TIMEOUT).is_ok());
+    }
+}